@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use csv::Reader;
+use serde::Deserialize;
+
+use crate::backtest::samples::TEAM_POINTS;
+
+// (player id or team, stat) -> what actually happened
+pub type Actuals = HashMap<(String, String), f32>;
+
+#[derive(Debug, Deserialize)]
+struct ScoreLoader {
+    home: String,
+    away: String,
+    home_score: u8,
+    away_score: u8,
+}
+
+/// `actuals/players.csv` has a `player_id` column and one column per stat,
+/// named like the samples. blank cells are skipped, so a file only needs
+/// the stats it has. `actuals/scores.csv` has the final score of each game
+pub fn load_actuals(slate_dir: &str) -> Actuals {
+    let mut actuals = HashMap::new();
+
+    let path = format!("{}/actuals/players.csv", slate_dir);
+    log::info!("loading player results from {}", path);
+    let mut reader =
+        Reader::from_path(&path).unwrap_or_else(|_| panic!("Failed to load file at {}", path));
+    let headers = reader.headers().unwrap().clone();
+    let id_ix = headers
+        .iter()
+        .position(|h| h == "player_id")
+        .unwrap_or_else(|| panic!("{} has no player_id column", path));
+    for record in reader.records() {
+        let record = record.expect("failed to parse player result");
+        for (ix, stat) in headers.iter().enumerate() {
+            let value = record.get(ix).unwrap_or("").trim();
+            if ix == id_ix || value.is_empty() {
+                continue;
+            }
+            let value = value
+                .parse::<f32>()
+                .unwrap_or_else(|_| panic!("bad {} value {:?} in {}", stat, value, path));
+            actuals.insert((record[id_ix].to_string(), stat.to_string()), value);
+        }
+    }
+
+    let path = format!("{}/actuals/scores.csv", slate_dir);
+    log::info!("loading final scores from {}", path);
+    let mut reader =
+        Reader::from_path(&path).unwrap_or_else(|_| panic!("Failed to load file at {}", path));
+    for row in reader.deserialize() {
+        let row: ScoreLoader = row.expect("failed to parse final score");
+        actuals.insert((row.home, TEAM_POINTS.into()), row.home_score as f32);
+        actuals.insert((row.away, TEAM_POINTS.into()), row.away_score as f32);
+    }
+    actuals
+}
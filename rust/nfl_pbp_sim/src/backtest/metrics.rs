@@ -0,0 +1,124 @@
+use rand::random;
+
+use crate::markets::MarketPrice;
+
+pub const N_BINS: usize = 10;
+// over/under lines are hung at these quantiles of each sample
+const LINE_QUANTILES: [f32; 5] = [0.1, 0.25, 0.5, 0.75, 0.9];
+// keeps a 0% or 100% touchdown call from costing infinite log loss
+const MIN_PROB: f32 = 0.001;
+
+fn bin(prob: f32) -> usize {
+    ((prob * N_BINS as f32) as usize).min(N_BINS - 1)
+}
+
+/// continuous ranked probability score of sorted samples, E|X - y| - E|X - X'| / 2
+pub fn crps(values: &[f32], actual: f32) -> f32 {
+    let n = values.len() as f32;
+    let abs_error = values.iter().map(|v| (v - actual).abs()).sum::<f32>() / n;
+    // for sorted values, the sum over pairs of |x_i - x_j| is sum of x_i * (2i - n + 1)
+    let spread = values
+        .iter()
+        .enumerate()
+        .map(|(ix, v)| v * (2.0 * ix as f32 - n + 1.0))
+        .sum::<f32>()
+        * 2.0
+        / (n * n);
+    abs_error - spread / 2.0
+}
+
+/// randomized probability integral transform, so discrete stats with a lot of
+/// zeros still come out uniform when the model is right
+pub fn pit(values: &[f32], actual: f32) -> f32 {
+    let below = values.partition_point(|v| *v < actual);
+    let at_or_below = values.partition_point(|v| *v <= actual);
+    let ties = (at_or_below - below) as f32;
+    (below as f32 + random::<f32>() * ties) / values.len() as f32
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CalibrationBin {
+    pub n: u32,
+    pub sum_prob: f32,
+    pub hits: f32,
+}
+
+impl CalibrationBin {
+    pub fn mean_prob(&self) -> f32 {
+        self.sum_prob / self.n as f32
+    }
+
+    pub fn hit_rate(&self) -> f32 {
+        self.hits / self.n as f32
+    }
+}
+
+/// running totals for one stat across every player and slate
+#[derive(Debug, Clone, Default)]
+pub struct StatMetrics {
+    pub n: u32,
+    pub sum_error: f32,
+    pub sum_abs_error: f32,
+    pub sum_crps: f32,
+    // anytime touchdown calls, only for touchdown stats
+    pub n_binary: u32,
+    pub sum_log_loss: f32,
+    // over/under calls by predicted probability of the over
+    pub calibration: [CalibrationBin; N_BINS],
+    pub pit: [u32; N_BINS],
+}
+
+impl StatMetrics {
+    /// score one sorted sample against what happened
+    pub fn add(&mut self, values: &[f32], actual: f32, is_touchdown: bool) {
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        self.n += 1;
+        self.sum_error += mean - actual;
+        self.sum_abs_error += (mean - actual).abs();
+        self.sum_crps += crps(values, actual);
+        self.pit[bin(pit(values, actual))] += 1;
+
+        if is_touchdown {
+            let prob = MarketPrice::over(values, 0.5)
+                .win
+                .clamp(MIN_PROB, 1.0 - MIN_PROB);
+            self.n_binary += 1;
+            self.sum_log_loss -= match actual >= 1.0 {
+                true => prob.ln(),
+                false => (1.0 - prob).ln(),
+            };
+        }
+
+        for q in LINE_QUANTILES {
+            let ix = (q * (values.len() - 1) as f32).round() as usize;
+            let line = values[ix].floor() + 0.5;
+            let prob = MarketPrice::over(values, line).win;
+            let calibration_bin = &mut self.calibration[bin(prob)];
+            calibration_bin.n += 1;
+            calibration_bin.sum_prob += prob;
+            if actual > line {
+                calibration_bin.hits += 1.0;
+            }
+        }
+    }
+
+    /// projected mean minus actual
+    pub fn bias(&self) -> f32 {
+        self.sum_error / self.n as f32
+    }
+
+    pub fn mae(&self) -> f32 {
+        self.sum_abs_error / self.n as f32
+    }
+
+    pub fn crps(&self) -> f32 {
+        self.sum_crps / self.n as f32
+    }
+
+    pub fn log_loss(&self) -> Option<f32> {
+        match self.n_binary > 0 {
+            true => Some(self.sum_log_loss / self.n_binary as f32),
+            false => None,
+        }
+    }
+}
@@ -0,0 +1,127 @@
+pub mod actuals;
+pub mod metrics;
+pub mod samples;
+
+use std::collections::HashMap;
+
+use crate::backtest::{
+    actuals::{load_actuals, Actuals},
+    metrics::{StatMetrics, N_BINS},
+    samples::{load_samples, Sample},
+};
+use crate::markets::player_stats::PlayerStat;
+
+fn is_touchdown_stat(stat: &str) -> bool {
+    [
+        PlayerStat::PassTds,
+        PlayerStat::RushTds,
+        PlayerStat::RecTds,
+        PlayerStat::Touchdowns,
+    ]
+    .iter()
+    .any(|td_stat| td_stat.to_string() == stat)
+}
+
+/// projection quality by stat, pooled over every slate added
+#[derive(Debug, Clone, Default)]
+pub struct Backtest {
+    pub slates: u32,
+    pub stats: HashMap<String, StatMetrics>,
+    // samples with no actual result, e.g. a player who was ruled out
+    pub unmatched: u32,
+}
+
+impl Backtest {
+    pub fn add_slate(&mut self, samples: &[Sample], actuals: &Actuals) {
+        self.slates += 1;
+        for sample in samples {
+            match actuals.get(&(sample.id.clone(), sample.stat.clone())) {
+                Some(actual) => self.stats.entry(sample.stat.clone()).or_default().add(
+                    &sample.values,
+                    *actual,
+                    is_touchdown_stat(&sample.stat),
+                ),
+                None => self.unmatched += 1,
+            }
+        }
+    }
+}
+
+/// score the stored samples of each slate against its `actuals` folder
+pub fn run_backtest(slate_dirs: &[String]) -> Backtest {
+    let mut backtest = Backtest::default();
+    for slate_dir in slate_dirs {
+        backtest.add_slate(&load_samples(slate_dir), &load_actuals(slate_dir));
+    }
+    log::info!(
+        "backtested {} slates, {} samples had no result",
+        backtest.slates,
+        backtest.unmatched
+    );
+    backtest
+}
+
+/// writes summary metrics, over/under calibration and PIT histograms by stat
+pub fn write_backtest(out_dir: &str, backtest: &Backtest) {
+    std::fs::create_dir_all(out_dir).unwrap();
+    let mut stats: Vec<(&String, &StatMetrics)> = backtest.stats.iter().collect();
+    stats.sort_by(|a, b| a.0.cmp(b.0));
+    let bin_edge = |ix: usize| format!("{:.1}", ix as f32 / N_BINS as f32);
+
+    let mut wtr = csv::Writer::from_path(format!("{}/backtest_metrics.csv", out_dir)).unwrap();
+    wtr.write_record(["stat", "n", "bias", "mae", "crps", "td_log_loss"])
+        .unwrap();
+    for (stat, metrics) in &stats {
+        wtr.write_record(&[
+            stat.to_string(),
+            metrics.n.to_string(),
+            format!("{:.3}", metrics.bias()),
+            format!("{:.3}", metrics.mae()),
+            format!("{:.3}", metrics.crps()),
+            metrics
+                .log_loss()
+                .map_or("".into(), |log_loss| format!("{:.4}", log_loss)),
+        ])
+        .unwrap();
+    }
+    wtr.flush().unwrap();
+
+    let mut wtr = csv::Writer::from_path(format!("{}/backtest_calibration.csv", out_dir)).unwrap();
+    wtr.write_record(["stat", "bin_lo", "bin_hi", "n", "mean_prob", "hit_rate"])
+        .unwrap();
+    for (stat, metrics) in &stats {
+        for (ix, calibration_bin) in metrics.calibration.iter().enumerate() {
+            if calibration_bin.n == 0 {
+                continue;
+            }
+            wtr.write_record(&[
+                stat.to_string(),
+                bin_edge(ix),
+                bin_edge(ix + 1),
+                calibration_bin.n.to_string(),
+                format!("{:.3}", calibration_bin.mean_prob()),
+                format!("{:.3}", calibration_bin.hit_rate()),
+            ])
+            .unwrap();
+        }
+    }
+    wtr.flush().unwrap();
+
+    // flat bars mean the predicted spread is right, a U means it's too narrow
+    let mut wtr = csv::Writer::from_path(format!("{}/backtest_pit.csv", out_dir)).unwrap();
+    wtr.write_record(["stat", "bin_lo", "bin_hi", "n", "share"])
+        .unwrap();
+    for (stat, metrics) in &stats {
+        for (ix, count) in metrics.pit.iter().enumerate() {
+            wtr.write_record(&[
+                stat.to_string(),
+                bin_edge(ix),
+                bin_edge(ix + 1),
+                count.to_string(),
+                format!("{:.3}", *count as f32 / metrics.n as f32),
+            ])
+            .unwrap();
+        }
+    }
+    wtr.flush().unwrap();
+}
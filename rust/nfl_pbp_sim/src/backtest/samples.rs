@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use csv::Reader;
+use serde::Deserialize;
+
+use crate::markets::player_stats::PlayerStat;
+use crate::params::{skill_player::Position, PlayerMeta};
+use crate::sim::box_score::{BoxScore, PlayerKey};
+
+pub const FANTASY_POINTS: &str = "fantasy_points";
+pub const TEAM_POINTS: &str = "team_points";
+
+const PLAYER_STATS: [PlayerStat; 13] = [
+    PlayerStat::PassAttempts,
+    PlayerStat::Completions,
+    PlayerStat::PassYards,
+    PlayerStat::PassTds,
+    PlayerStat::Interceptions,
+    PlayerStat::Carries,
+    PlayerStat::RushYards,
+    PlayerStat::RushTds,
+    PlayerStat::Targets,
+    PlayerStat::Receptions,
+    PlayerStat::RecYards,
+    PlayerStat::RecTds,
+    PlayerStat::Touchdowns,
+];
+
+fn is_passing_stat(stat: PlayerStat) -> bool {
+    matches!(
+        stat,
+        PlayerStat::PassAttempts
+            | PlayerStat::Completions
+            | PlayerStat::PassYards
+            | PlayerStat::PassTds
+            | PlayerStat::Interceptions
+    )
+}
+
+/// every simulated value of one stat for a player, or a team for `TEAM_POINTS`
+#[derive(Debug, Clone)]
+pub struct Sample {
+    pub id: String,
+    pub stat: String,
+    // sorted
+    pub values: Vec<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SampleLoader {
+    id: String,
+    stat: String,
+    values: String,
+}
+
+fn sorted(mut values: Vec<f32>) -> Vec<f32> {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    values
+}
+
+/// the per-sim values a backtest scores: box score stats and draftkings points
+/// for every player, and final points for every team
+pub fn sample_box_scores(
+    box_scores: &[Vec<BoxScore>],
+    player_meta: &HashMap<PlayerKey, PlayerMeta>,
+) -> Vec<Sample> {
+    let mut samples = vec![];
+    let mut player_ids: Vec<(&String, &PlayerMeta)> = player_meta
+        .iter()
+        .filter_map(|(key, meta)| match key {
+            PlayerKey::NflId(player_id) => Some((player_id, meta)),
+            _ => None,
+        })
+        .collect();
+    player_ids.sort_by_key(|(player_id, _)| *player_id);
+    for (player_id, meta) in player_ids {
+        for stat in PLAYER_STATS {
+            if is_passing_stat(stat) && meta.pos != Position::Quarterback {
+                continue;
+            }
+            samples.push(Sample {
+                id: player_id.clone(),
+                stat: stat.to_string(),
+                values: sorted(
+                    box_scores
+                        .iter()
+                        .map(|sim| stat.sim_value(sim, player_id))
+                        .collect(),
+                ),
+            });
+        }
+        samples.push(Sample {
+            id: player_id.clone(),
+            stat: FANTASY_POINTS.into(),
+            values: sorted(
+                box_scores
+                    .iter()
+                    .map(|sim| {
+                        sim.iter()
+                            .find(|bs| {
+                                bs.passers.contains_key(player_id)
+                                    || bs.skill_players.contains_key(player_id)
+                            })
+                            .map_or(0.0, |bs| bs.player_fantasy_points(player_id).draftkings)
+                    })
+                    .collect(),
+            ),
+        });
+    }
+
+    let n_games = box_scores.first().map_or(0, |sim| sim.len());
+    for game_ix in 0..n_games {
+        let game = &box_scores[0][game_ix];
+        for (team, home) in [(&game.home, true), (&game.away, false)] {
+            samples.push(Sample {
+                id: team.clone(),
+                stat: TEAM_POINTS.into(),
+                values: sorted(
+                    box_scores
+                        .iter()
+                        .map(|sim| match home {
+                            true => sim[game_ix].score.home as f32,
+                            false => sim[game_ix].score.away as f32,
+                        })
+                        .collect(),
+                ),
+            });
+        }
+    }
+    samples
+}
+
+/// one row per player and stat, with the sims space separated
+pub fn write_samples(slate_dir: &str, samples: &[Sample]) {
+    let proj_path = format!("{}/projections", slate_dir);
+    std::fs::create_dir_all(&proj_path).unwrap();
+    let mut wtr = csv::Writer::from_path(format!("{}/samples.csv", proj_path)).unwrap();
+    wtr.write_record(["id", "stat", "values"]).unwrap();
+    for sample in samples {
+        let values: Vec<String> = sample.values.iter().map(|v| v.to_string()).collect();
+        wtr.write_record(&[sample.id.clone(), sample.stat.clone(), values.join(" ")])
+            .unwrap();
+    }
+    wtr.flush().unwrap();
+}
+
+pub fn load_samples(slate_dir: &str) -> Vec<Sample> {
+    let path = format!("{}/projections/samples.csv", slate_dir);
+    log::info!("loading samples from {}", path);
+    let mut reader =
+        Reader::from_path(&path).unwrap_or_else(|_| panic!("Failed to load file at {}", path));
+    reader
+        .deserialize()
+        .map(|row| {
+            let row: SampleLoader = row.expect("failed to parse sample row");
+            let values = row
+                .values
+                .split_whitespace()
+                .map(|v| v.parse::<f32>().expect("failed to parse sample value"))
+                .collect();
+            Sample {
+                id: row.id,
+                stat: row.stat,
+                values: sorted(values),
+            }
+        })
+        .collect()
+}
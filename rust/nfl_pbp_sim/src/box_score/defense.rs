@@ -1,8 +1,21 @@
 use core::panic;
 use std::collections::HashMap;
 
+use serde::Serialize;
+
 use crate::sim::{box_score::BoxScore, play_result::TurnoverOutcome};
 
+/// (min, max, fantasy points) of each points-allowed tier, the same on every DFS site
+pub const POINTS_ALLOWED_TIERS: [(u8, u8, f32); 7] = [
+    (0, 0, 10.0),
+    (1, 6, 7.0),
+    (7, 13, 4.0),
+    (14, 20, 1.0),
+    (21, 27, 0.0),
+    (28, 34, -1.0),
+    (35, u8::MAX, -4.0),
+];
+
 #[derive(Debug, Clone)]
 pub struct DefenseBoxScore {
     pub sacks: u8,
@@ -75,15 +88,10 @@ impl DefenseBoxScore {
     }
 
     pub fn standard_fantasy_points(&self) -> f32 {
-        let mut points = match self.points_allowed {
-            0 => 10.0,
-            1..=6 => 7.0,
-            7..=13 => 4.0,
-            14..=20 => 1.0,
-            21..=27 => 0.0,
-            28..=34 => -1.0,
-            _ => -4.0,
-        };
+        let (_, _, mut points) = POINTS_ALLOWED_TIERS
+            .iter()
+            .find(|(_, max, _)| self.points_allowed <= *max)
+            .unwrap();
 
         points += 1.0 * (self.sacks as f32);
         points += 2.0 * (self.interceptions as f32);
@@ -95,7 +103,7 @@ impl DefenseBoxScore {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct DefenseProjection {
     pub sacks: f32,
     pub interceptions: f32,
@@ -104,6 +112,8 @@ pub struct DefenseProjection {
     pub safeties: f32,
     pub blocked_kicks: f32,
     pub points_allowed: f32,
+    #[serde(skip)]
+    pub points_allowed_sampled: Vec<u8>,
     pub penalties: f32,
     pub penalty_yards: f32,
 }
@@ -118,6 +128,7 @@ impl DefenseProjection {
             safeties: 0.0,
             blocked_kicks: 0.0,
             points_allowed: 0.0,
+            points_allowed_sampled: vec![],
             penalties: 0.0,
             penalty_yards: 0.0,
         }
@@ -131,15 +142,17 @@ impl DefenseProjection {
         self.safeties += stats.safeties as f32 / n_sims;
         self.blocked_kicks += stats.blocked_kicks as f32 / n_sims;
         self.points_allowed += stats.points_allowed as f32 / n_sims;
+        self.points_allowed_sampled.push(stats.points_allowed);
 
         self.penalties += stats.defensive_penalties as f32 / n_sims;
         self.penalty_yards += stats.defensive_penalty_yards as f32 / n_sims;
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct OffenseProjection {
     pub points: f32,
+    #[serde(skip)]
     pub points_sampled: Vec<u8>,
     pub plays_called: f32,
     pub rushes: f32,
@@ -183,7 +196,7 @@ impl OffenseProjection {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct SpecialTeamsProjection {
     pub fg_attempts: f32,
     pub fg_made: f32,
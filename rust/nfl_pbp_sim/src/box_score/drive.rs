@@ -0,0 +1,205 @@
+use serde::Serialize;
+
+use crate::{
+    sim::{
+        box_score::BoxScore,
+        play_result::{FieldGoalResult, PlayResult},
+    },
+    start::HomeAway,
+    state::{
+        clock::{GameClock, Quarter},
+        down::{Down, DownToGo, PlayState, ToGo},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DriveResult {
+    Touchdown,
+    FieldGoal,
+    MissedFieldGoal,
+    Punt,
+    Turnover,
+    TurnoverOnDowns,
+    EndOfHalf,
+    Safety,
+}
+
+impl DriveResult {
+    pub const ALL: [DriveResult; 8] = [
+        DriveResult::Touchdown,
+        DriveResult::FieldGoal,
+        DriveResult::MissedFieldGoal,
+        DriveResult::Punt,
+        DriveResult::Turnover,
+        DriveResult::TurnoverOnDowns,
+        DriveResult::EndOfHalf,
+        DriveResult::Safety,
+    ];
+}
+
+impl std::fmt::Display for DriveResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DriveResult::Touchdown => write!(f, "touchdown"),
+            DriveResult::FieldGoal => write!(f, "field_goal"),
+            DriveResult::MissedFieldGoal => write!(f, "missed_field_goal"),
+            DriveResult::Punt => write!(f, "punt"),
+            DriveResult::Turnover => write!(f, "turnover"),
+            DriveResult::TurnoverOnDowns => write!(f, "turnover_on_downs"),
+            DriveResult::EndOfHalf => write!(f, "end_of_half"),
+            DriveResult::Safety => write!(f, "safety"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Drive {
+    pub team: HomeAway,
+    pub quarter: Quarter,
+    // seconds left in the quarter at the first snap
+    pub start_seconds_remaining: u16,
+    pub start_yards_to_goal: u8,
+    pub end_yards_to_goal: u8,
+    // snaps from scrimmage, including penalties and kicks
+    pub plays: u8,
+    pub first_downs: u8,
+    // time of possession
+    pub seconds: u16,
+    // offensive points, including the try after a touchdown
+    pub points: u8,
+    pub red_zone: bool,
+    // none while the drive is still going
+    pub result: Option<DriveResult>,
+}
+
+impl Drive {
+    pub fn new(down_to_go: &DownToGo, clock: &GameClock) -> Drive {
+        Drive {
+            team: down_to_go.possession,
+            quarter: clock.quarter,
+            start_seconds_remaining: clock.seconds_remaining,
+            start_yards_to_goal: down_to_go.yards_to_goal.0,
+            end_yards_to_goal: down_to_go.yards_to_goal.0,
+            plays: 0,
+            first_downs: 0,
+            seconds: 0,
+            points: 0,
+            red_zone: down_to_go.is_redzone(),
+            result: None,
+        }
+    }
+
+    pub fn yards(&self) -> i16 {
+        self.start_yards_to_goal as i16 - self.end_yards_to_goal as i16
+    }
+
+    /// punted without picking up a first down
+    pub fn is_three_and_out(&self) -> bool {
+        self.result == Some(DriveResult::Punt) && self.first_downs == 0
+    }
+
+    /// record a snap, closing out the drive if the offense no longer has the ball
+    pub fn add_play(&mut self, play: &PlayResult, next_state: &PlayState, points: u8) {
+        self.plays += 1;
+        self.points += points;
+        self.result = match play {
+            PlayResult::Punt(_, _) => Some(DriveResult::Punt),
+            PlayResult::FieldGoal(FieldGoalResult::AttemptedFg(true)) => {
+                Some(DriveResult::FieldGoal)
+            }
+            PlayResult::FieldGoal(_) => Some(DriveResult::MissedFieldGoal),
+            _ => match next_state {
+                PlayState::Down(dtg) if dtg.possession == self.team => {
+                    self.end_yards_to_goal = dtg.yards_to_goal.0;
+                    self.red_zone |= dtg.is_redzone();
+                    if dtg.down == Down::First && dtg.to_go == ToGo::first_to_go(dtg.yards_to_goal)
+                    {
+                        self.first_downs += 1;
+                    }
+                    None
+                }
+                PlayState::PointAfterTouchdown(team) if *team == self.team => {
+                    self.end_yards_to_goal = 0;
+                    self.red_zone = true;
+                    Some(DriveResult::Touchdown)
+                }
+                PlayState::PuntAfterSafety(team) if *team == self.team => {
+                    self.end_yards_to_goal = 100;
+                    Some(DriveResult::Safety)
+                }
+                PlayState::Down(dtg) if !play.is_turnover() => {
+                    self.end_yards_to_goal = dtg.yards_to_goal.flip().0;
+                    Some(DriveResult::TurnoverOnDowns)
+                }
+                _ => Some(DriveResult::Turnover),
+            },
+        };
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default, Serialize)]
+pub struct DriveProjection {
+    pub drives: f32,
+    pub points: f32,
+    pub touchdowns: f32,
+    pub red_zone_trips: f32,
+    pub red_zone_touchdowns: f32,
+    pub three_and_outs: f32,
+}
+
+impl DriveProjection {
+    pub fn new() -> DriveProjection {
+        DriveProjection {
+            drives: 0.0,
+            points: 0.0,
+            touchdowns: 0.0,
+            red_zone_trips: 0.0,
+            red_zone_touchdowns: 0.0,
+            three_and_outs: 0.0,
+        }
+    }
+
+    pub fn add(&mut self, team: &String, box_score: &BoxScore, n_sims: f32) {
+        let home_away = HomeAway::home_if_true(team == &box_score.home);
+        for drive in box_score.drives.iter().filter(|d| d.team == home_away) {
+            let touchdown = drive.result == Some(DriveResult::Touchdown);
+            self.drives += 1.0 / n_sims;
+            self.points += drive.points as f32 / n_sims;
+            if touchdown {
+                self.touchdowns += 1.0 / n_sims;
+            }
+            if drive.red_zone {
+                self.red_zone_trips += 1.0 / n_sims;
+                if touchdown {
+                    self.red_zone_touchdowns += 1.0 / n_sims;
+                }
+            }
+            if drive.is_three_and_out() {
+                self.three_and_outs += 1.0 / n_sims;
+            }
+        }
+    }
+
+    fn rate(numerator: f32, denominator: f32) -> f32 {
+        match denominator > 0.0 {
+            true => numerator / denominator,
+            false => 0.0,
+        }
+    }
+
+    pub fn points_per_drive(&self) -> f32 {
+        DriveProjection::rate(self.points, self.drives)
+    }
+
+    pub fn touchdown_rate(&self) -> f32 {
+        DriveProjection::rate(self.touchdowns, self.drives)
+    }
+
+    pub fn red_zone_touchdown_rate(&self) -> f32 {
+        DriveProjection::rate(self.red_zone_touchdowns, self.red_zone_trips)
+    }
+
+    pub fn three_and_out_rate(&self) -> f32 {
+        DriveProjection::rate(self.three_and_outs, self.drives)
+    }
+}
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use serde::Serialize;
+
 #[derive(Debug, Clone)]
 pub struct KickerBoxScore {
     pub fgs_made: Vec<u8>,
@@ -7,6 +9,10 @@ pub struct KickerBoxScore {
     pub num_punts: u8,
     pub pats_made: u8,
     pub pats_missed: u8,
+    // tries where the offense went for two instead
+    pub two_point_attempts: u8,
+    pub kickoffs: u8,
+    pub onside_kicks: u8,
 }
 
 impl KickerBoxScore {
@@ -17,6 +23,9 @@ impl KickerBoxScore {
             num_punts: 0,
             pats_made: 0,
             pats_missed: 0,
+            two_point_attempts: 0,
+            kickoffs: 0,
+            onside_kicks: 0,
         }
     }
 
@@ -39,6 +48,17 @@ impl KickerBoxScore {
         }
     }
 
+    pub fn add_two_point_attempt(&mut self) {
+        self.two_point_attempts += 1;
+    }
+
+    pub fn add_kickoff(&mut self, onside: bool) {
+        self.kickoffs += 1;
+        if onside {
+            self.onside_kicks += 1;
+        }
+    }
+
     pub fn new_map(home: String, away: String) -> HashMap<String, KickerBoxScore> {
         let mut kickers = HashMap::new();
         kickers.insert(home, KickerBoxScore::new());
@@ -60,7 +80,7 @@ impl KickerBoxScore {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct KickerProjection {
     pub fgs_made: f32,
     pub fgs_attempted: f32,
@@ -68,6 +88,10 @@ pub struct KickerProjection {
     pub fg_made_30_39: f32,
     pub fg_made_40_49: f32,
     pub fg_made_50o: f32,
+    pub fg_att_u30: f32,
+    pub fg_att_30_39: f32,
+    pub fg_att_40_49: f32,
+    pub fg_att_50o: f32,
     pub pats_made: f32,
     pub pats_attempted: f32,
     pub punts: f32,
@@ -82,6 +106,10 @@ impl KickerProjection {
             fg_made_30_39: 0.0,
             fg_made_40_49: 0.0,
             fg_made_50o: 0.0,
+            fg_att_u30: 0.0,
+            fg_att_30_39: 0.0,
+            fg_att_40_49: 0.0,
+            fg_att_50o: 0.0,
             pats_made: 0.0,
             pats_attempted: 0.0,
             punts: 0.0,
@@ -1,4 +1,5 @@
 pub mod defense;
+pub mod drive;
 pub mod kicking;
 pub mod passing;
 pub mod skill_player;
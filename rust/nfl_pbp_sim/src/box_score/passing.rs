@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use serde::Serialize;
+
 use crate::{
     params::GameParams,
     sim::play_result::{DropbackOutcome, ReceivingYards, SackOutcome, TargetOutcome},
@@ -18,6 +20,9 @@ pub struct PassingBoxScore {
     pub fumbles_lost: u8,
     pub scrambles: u8,
     pub kneels: u8,
+    // only counted when plays are valued with an EP table
+    pub dropbacks: u16,
+    pub epa: f32,
 }
 
 impl PassingBoxScore {
@@ -34,6 +39,8 @@ impl PassingBoxScore {
             fumbles_lost: 0,
             scrambles: 0,
             kneels: 0,
+            dropbacks: 0,
+            epa: 0.0,
         }
     }
 
@@ -97,6 +104,11 @@ impl PassingBoxScore {
         }
     }
 
+    pub fn add_dropback_epa(&mut self, epa: f32) {
+        self.dropbacks += 1;
+        self.epa += epa;
+    }
+
     pub fn add_two_point_conversion(&mut self) {
         self.two_point_conversions += 1;
     }
@@ -112,7 +124,7 @@ impl PassingBoxScore {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct PassingProjection {
     pub attempts: f32,
     pub completions: f32,
@@ -124,7 +136,9 @@ pub struct PassingProjection {
     pub two_point_conversions: f32,
     pub scrambles: f32,
     pub kneels: f32,
+    #[serde(skip)]
     pub yards_sampled: Vec<i16>,
+    #[serde(skip)]
     pub tds_sampled: Vec<u8>,
 }
 
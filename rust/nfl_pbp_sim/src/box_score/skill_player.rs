@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use serde::Serialize;
+
 use crate::{
     params::GameParams,
     sim::play_result::{ReceivingYards, RushingOutcome, TargetOutcome},
@@ -19,10 +21,14 @@ pub struct SkillPlayerBoxScore {
     pub air_yards: i16,
     pub yards_after_catch: i16,
     pub receiving_touchdowns: u8,
+    pub longest_reception: i16,
     // misc
     pub fumbles_lost: u8,
     pub return_touchdowns: u8,
     pub two_point_conversions: u8,
+    // only counted when plays are valued with an EP table
+    pub rush_epa: f32,
+    pub rec_epa: f32,
 }
 
 impl SkillPlayerBoxScore {
@@ -37,9 +43,12 @@ impl SkillPlayerBoxScore {
             air_yards: 0,
             yards_after_catch: 0,
             receiving_touchdowns: 0,
+            longest_reception: 0,
             fumbles_lost: 0,
             return_touchdowns: 0,
             two_point_conversions: 0,
+            rush_epa: 0.0,
+            rec_epa: 0.0,
         }
     }
 
@@ -65,6 +74,14 @@ impl SkillPlayerBoxScore {
         self.rushing_yards += yards_gained as i16;
     }
 
+    pub fn add_rush_epa(&mut self, epa: f32) {
+        self.rush_epa += epa;
+    }
+
+    pub fn add_rec_epa(&mut self, epa: f32) {
+        self.rec_epa += epa;
+    }
+
     pub fn add_incomplete_target(&mut self, air_yards: i8) {
         self.targets += 1;
         self.air_yards += air_yards as i16;
@@ -76,6 +93,7 @@ impl SkillPlayerBoxScore {
         self.receiving_yards += yards.total() as i16;
         self.air_yards += yards.air_yards as i16;
         self.yards_after_catch += yards.yards_after_catch as i16;
+        self.longest_reception = self.longest_reception.max(yards.total() as i16);
     }
 
     pub fn add_fumble_lost(&mut self) {
@@ -152,7 +170,7 @@ impl SkillPlayerBoxScore {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct SkillPlayerProjection {
     // rushing
     pub carries: f32,
@@ -171,11 +189,17 @@ pub struct SkillPlayerProjection {
     pub two_point_conversions: f32,
 
     // for medians
+    #[serde(skip)]
     pub rushing_yards_sampled: Vec<i16>,
+    #[serde(skip)]
     pub receiving_yards_sampled: Vec<i16>,
+    #[serde(skip)]
     pub total_yards_sampled: Vec<i16>,
+    #[serde(skip)]
     pub rushing_tds_sampled: Vec<u8>,
+    #[serde(skip)]
     pub receiving_tds_sampled: Vec<u8>,
+    #[serde(skip)]
     pub total_tds_sampled: Vec<u8>,
 }
 
@@ -0,0 +1,405 @@
+use std::collections::HashMap;
+
+use csv::Reader;
+use rayon::prelude::*;
+use serde::Deserialize;
+
+use crate::dfs::showdown::ShowdownRules;
+use crate::dfs::{sim_player_points, DfsPlayer, DfsSite};
+use crate::params::skill_player::Position;
+use crate::sim::box_score::{BoxScore, PlayerKey};
+use crate::util::stats::random_discrete;
+
+const MAX_FIELD_LINEUP_ATTEMPTS: u32 = 1_000;
+
+#[derive(Debug, Clone)]
+pub struct RosterSlot {
+    // empty means any position is eligible
+    pub positions: Vec<Position>,
+    pub points_mult: f32,
+    pub salary_mult: f32,
+}
+
+impl RosterSlot {
+    fn new(positions: Vec<Position>) -> RosterSlot {
+        RosterSlot {
+            positions,
+            points_mult: 1.0,
+            salary_mult: 1.0,
+        }
+    }
+
+    fn is_eligible(&self, pos: Position) -> bool {
+        self.positions.is_empty() || self.positions.contains(&pos)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RosterRules {
+    pub site: DfsSite,
+    pub salary_cap: u32,
+    pub slots: Vec<RosterSlot>,
+}
+
+impl RosterRules {
+    fn classic_slots() -> Vec<RosterSlot> {
+        let rb = vec![Position::Halfback, Position::Fullback];
+        let flex = vec![
+            Position::Halfback,
+            Position::Fullback,
+            Position::WideReceiver,
+            Position::TightEnd,
+        ];
+        vec![
+            RosterSlot::new(vec![Position::Quarterback]),
+            RosterSlot::new(rb.clone()),
+            RosterSlot::new(rb),
+            RosterSlot::new(vec![Position::WideReceiver]),
+            RosterSlot::new(vec![Position::WideReceiver]),
+            RosterSlot::new(vec![Position::WideReceiver]),
+            RosterSlot::new(vec![Position::TightEnd]),
+            RosterSlot::new(flex),
+            RosterSlot::new(vec![Position::Defense]),
+        ]
+    }
+
+    pub fn draftkings_classic() -> RosterRules {
+        RosterRules {
+            site: DfsSite::DraftKings,
+            salary_cap: 50_000,
+            slots: RosterRules::classic_slots(),
+        }
+    }
+
+    pub fn fanduel_classic() -> RosterRules {
+        RosterRules {
+            site: DfsSite::FanDuel,
+            salary_cap: 60_000,
+            slots: RosterRules::classic_slots(),
+        }
+    }
+
+    pub fn showdown(rules: &ShowdownRules) -> RosterRules {
+        let mut slots = vec![RosterSlot {
+            positions: vec![],
+            points_mult: rules.captain_points_mult,
+            salary_mult: rules.captain_salary_mult,
+        }];
+        for _ in 0..rules.n_flex {
+            slots.push(RosterSlot::new(vec![]));
+        }
+        RosterRules {
+            site: rules.site,
+            salary_cap: rules.salary_cap,
+            slots,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContestLineupLoader {
+    pub lineup_id: String,
+    pub player_key: String,
+    // 1.5 for a showdown captain, defaults to 1.0
+    pub multiplier: Option<f32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ContestLineup {
+    pub id: String,
+    pub players: Vec<(PlayerKey, f32)>,
+}
+
+impl ContestLineup {
+    /// load lineups from a long file, one row per rostered player
+    pub fn load(path: &str) -> Vec<ContestLineup> {
+        log::info!("loading lineups from {}", path);
+        let mut reader =
+            Reader::from_path(path).unwrap_or_else(|_| panic!("Failed to load file at {}", path));
+
+        let mut lineups: Vec<ContestLineup> = vec![];
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for row in reader.deserialize() {
+            let loader: ContestLineupLoader = row.expect("failed to parse lineup");
+            let ix = *index.entry(loader.lineup_id.clone()).or_insert_with(|| {
+                lineups.push(ContestLineup {
+                    id: loader.lineup_id.clone(),
+                    players: vec![],
+                });
+                lineups.len() - 1
+            });
+            lineups[ix].players.push((
+                PlayerKey::from_string(&loader.player_key),
+                loader.multiplier.unwrap_or(1.0),
+            ));
+        }
+        lineups
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OwnershipLoader {
+    pub player_key: String,
+    pub ownership: f32,
+}
+
+pub fn load_ownership(path: &str) -> HashMap<PlayerKey, f32> {
+    log::info!("loading ownership from {}", path);
+    let mut reader =
+        Reader::from_path(path).unwrap_or_else(|_| panic!("Failed to load file at {}", path));
+    reader
+        .deserialize()
+        .map(|row| {
+            let loader: OwnershipLoader = row.expect("failed to parse ownership");
+            (PlayerKey::from_string(&loader.player_key), loader.ownership)
+        })
+        .collect()
+}
+
+/// draw an opponent field slot by slot, weighting each eligible player by ownership
+/// and throwing out lineups that go over the cap
+pub fn synthetic_field(
+    rules: &RosterRules,
+    players: &[DfsPlayer],
+    ownership: &HashMap<PlayerKey, f32>,
+    n_lineups: usize,
+) -> Vec<ContestLineup> {
+    let mut field = Vec::with_capacity(n_lineups);
+    let mut attempts = 0;
+    while field.len() < n_lineups {
+        attempts += 1;
+        if attempts > MAX_FIELD_LINEUP_ATTEMPTS * n_lineups as u32 {
+            panic!(
+                "could only build {} of {} field lineups under the cap",
+                field.len(),
+                n_lineups
+            );
+        }
+        if let Some(mut lineup) = sample_field_lineup(rules, players, ownership) {
+            lineup.id = format!("field-{}", field.len() + 1);
+            field.push(lineup);
+        }
+    }
+    field
+}
+
+fn sample_field_lineup(
+    rules: &RosterRules,
+    players: &[DfsPlayer],
+    ownership: &HashMap<PlayerKey, f32>,
+) -> Option<ContestLineup> {
+    let mut used: Vec<usize> = vec![];
+    let mut salary = 0.0;
+    let mut lineup = vec![];
+    for slot in &rules.slots {
+        let choices: Vec<(usize, f32)> = players
+            .iter()
+            .enumerate()
+            .filter(|(ii, p)| slot.is_eligible(p.pos) && !used.contains(ii))
+            .map(|(ii, p)| (ii, *ownership.get(&p.key).unwrap_or(&0.0)))
+            .filter(|(_, own)| *own > 0.0)
+            .collect();
+        let ii = random_discrete(choices).ok()?;
+        used.push(ii);
+        salary += players[ii].salary as f32 * slot.salary_mult;
+        lineup.push((players[ii].key.clone(), slot.points_mult));
+    }
+    match salary <= rules.salary_cap as f32 {
+        true => Some(ContestLineup {
+            id: String::new(),
+            players: lineup,
+        }),
+        false => None,
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Payout {
+    pub min_rank: u32,
+    pub max_rank: u32,
+    pub payout: f32,
+}
+
+impl Payout {
+    pub fn load(path: &str) -> Vec<Payout> {
+        log::info!("loading payouts from {}", path);
+        let mut reader =
+            Reader::from_path(path).unwrap_or_else(|_| panic!("Failed to load file at {}", path));
+        reader
+            .deserialize()
+            .map(|row| row.expect("failed to parse payout"))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Contest {
+    pub site: DfsSite,
+    pub entry_fee: f32,
+    pub payouts: Vec<Payout>,
+}
+
+impl Contest {
+    /// prize for finishing at a 1-indexed rank
+    pub fn payout(&self, rank: u32) -> f32 {
+        self.payouts
+            .iter()
+            .find(|p| p.min_rank <= rank && rank <= p.max_rank)
+            .map(|p| p.payout)
+            .unwrap_or(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct LineupTotals {
+    points: f64,
+    payout: f64,
+    cashes: u32,
+    top_1_pct: u32,
+    wins: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ContestLineupResult {
+    pub id: String,
+    pub mean_points: f32,
+    pub mean_payout: f32,
+    pub roi: f32,
+    pub cash_rate: f32,
+    pub top_1_pct_rate: f32,
+    pub win_rate: f32,
+}
+
+/// score our lineups and the field in every sim, rank everyone together and pay out.
+/// tied lineups split the prizes for the ranks they span
+pub fn simulate_contest(
+    contest: &Contest,
+    lineups: &[ContestLineup],
+    field: &[ContestLineup],
+    sims: &[Vec<BoxScore>],
+) -> Vec<ContestLineupResult> {
+    let mut player_index: HashMap<PlayerKey, usize> = HashMap::new();
+    let entries: Vec<Vec<(usize, f32)>> = lineups
+        .iter()
+        .chain(field.iter())
+        .map(|lineup| {
+            lineup
+                .players
+                .iter()
+                .map(|(key, mult)| {
+                    let n_players = player_index.len();
+                    (*player_index.entry(key.clone()).or_insert(n_players), *mult)
+                })
+                .collect()
+        })
+        .collect();
+    let n_entries = entries.len() as u32;
+    let top_1_pct_rank = ((n_entries as f32) * 0.01).ceil().max(1.0) as u32;
+
+    let totals = sims
+        .par_iter()
+        .fold(
+            || vec![LineupTotals::default(); lineups.len()],
+            |mut totals, box_scores| {
+                let mut points = vec![0.0; player_index.len()];
+                for (key, fp) in sim_player_points(box_scores) {
+                    if let Some(ix) = player_index.get(&key) {
+                        points[*ix] = contest.site.points(&fp);
+                    }
+                }
+                let scores: Vec<f32> = entries
+                    .iter()
+                    .map(|entry| entry.iter().map(|(ix, mult)| points[*ix] * mult).sum())
+                    .collect();
+                let mut order: Vec<usize> = (0..entries.len()).collect();
+                order.sort_by(|a, b| scores[*b].partial_cmp(&scores[*a]).unwrap());
+
+                let mut start = 0;
+                while start < order.len() {
+                    let mut end = start + 1;
+                    while end < order.len() && scores[order[end]] == scores[order[start]] {
+                        end += 1;
+                    }
+                    let first_rank = start as u32 + 1;
+                    let pool: f32 = (first_rank..=end as u32).map(|r| contest.payout(r)).sum();
+                    let split = pool / (end - start) as f32;
+                    for ix in &order[start..end] {
+                        if *ix >= lineups.len() {
+                            continue;
+                        }
+                        let lt = &mut totals[*ix];
+                        lt.points += scores[*ix] as f64;
+                        lt.payout += split as f64;
+                        lt.cashes += (split > 0.0) as u32;
+                        lt.top_1_pct += (first_rank <= top_1_pct_rank) as u32;
+                        lt.wins += (first_rank == 1) as u32;
+                    }
+                    start = end;
+                }
+                totals
+            },
+        )
+        .reduce(
+            || vec![LineupTotals::default(); lineups.len()],
+            |mut a, b| {
+                for (x, y) in a.iter_mut().zip(b.iter()) {
+                    x.points += y.points;
+                    x.payout += y.payout;
+                    x.cashes += y.cashes;
+                    x.top_1_pct += y.top_1_pct;
+                    x.wins += y.wins;
+                }
+                a
+            },
+        );
+
+    let n_sims = sims.len() as f32;
+    lineups
+        .iter()
+        .zip(totals)
+        .map(|(lineup, lt)| {
+            let mean_payout = lt.payout as f32 / n_sims;
+            ContestLineupResult {
+                id: lineup.id.clone(),
+                mean_points: lt.points as f32 / n_sims,
+                mean_payout,
+                roi: (mean_payout - contest.entry_fee) / contest.entry_fee,
+                cash_rate: lt.cashes as f32 / n_sims,
+                top_1_pct_rate: lt.top_1_pct as f32 / n_sims,
+                win_rate: lt.wins as f32 / n_sims,
+            }
+        })
+        .collect()
+}
+
+pub fn write_contest_results(slate_dir: &str, contest_name: &str, results: &[ContestLineupResult]) {
+    let proj_path = format!("{}/projections", slate_dir);
+    std::fs::create_dir_all(&proj_path).unwrap();
+    let path = format!("{}/contest_{}.csv", proj_path, contest_name);
+    let mut wtr = csv::Writer::from_path(path).unwrap();
+    wtr.write_record([
+        "lineup_id",
+        "mean_points",
+        "mean_payout",
+        "roi",
+        "cash%",
+        "top1%",
+        "win%",
+    ])
+    .unwrap();
+    let mut results: Vec<&ContestLineupResult> = results.iter().collect();
+    results.sort_by(|a, b| b.roi.partial_cmp(&a.roi).unwrap());
+    for result in results {
+        wtr.write_record(&[
+            result.id.clone(),
+            format!("{:.2}", result.mean_points),
+            format!("{:.2}", result.mean_payout),
+            format!("{:.1}%", 100.0 * result.roi),
+            format!("{:.1}%", 100.0 * result.cash_rate),
+            format!("{:.2}%", 100.0 * result.top_1_pct_rate),
+            format!("{:.3}%", 100.0 * result.win_rate),
+        ])
+        .unwrap();
+    }
+    wtr.flush().unwrap();
+}
@@ -0,0 +1,83 @@
+pub mod contest;
+pub mod showdown;
+
+use std::collections::HashMap;
+
+use csv::Reader;
+use serde::Deserialize;
+
+use crate::params::skill_player::Position;
+use crate::params::PlayerMeta;
+use crate::sim::box_score::{BoxScore, FantasyPoints, PlayerFantasyPoints, PlayerKey};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum DfsSite {
+    DraftKings,
+    FanDuel,
+}
+
+impl DfsSite {
+    pub fn points(&self, fp: &PlayerFantasyPoints) -> f32 {
+        match self {
+            DfsSite::DraftKings => fp.draftkings,
+            DfsSite::FanDuel => fp.fanduel,
+        }
+    }
+}
+
+impl std::fmt::Display for DfsSite {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DfsSite::DraftKings => write!(f, "draftkings"),
+            DfsSite::FanDuel => write!(f, "fanduel"),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DfsPlayerLoader {
+    // same format as `PlayerKey::to_string`, e.g. 00-0036264 or GB-DEF
+    pub player_key: String,
+    pub salary: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct DfsPlayer {
+    pub key: PlayerKey,
+    pub name: String,
+    pub pos: Position,
+    pub team: String,
+    pub salary: u32,
+}
+
+impl DfsPlayer {
+    /// load a salary file, dropping anyone we are not simming
+    pub fn load(path: &str, player_meta: &HashMap<PlayerKey, PlayerMeta>) -> Vec<DfsPlayer> {
+        log::info!("loading salaries from {}", path);
+        let mut reader =
+            Reader::from_path(path).unwrap_or_else(|_| panic!("Failed to load file at {}", path));
+
+        let mut players = vec![];
+        for row in reader.deserialize() {
+            let loader: DfsPlayerLoader = row.expect("failed to parse salary");
+            let key = PlayerKey::from_string(&loader.player_key);
+            match player_meta.get(&key) {
+                Some(meta) => players.push(DfsPlayer {
+                    key,
+                    name: meta.name.clone(),
+                    pos: meta.pos,
+                    team: meta.team.clone(),
+                    salary: loader.salary,
+                }),
+                None => log::warn!("no sim params for {}, skipping", loader.player_key),
+            }
+        }
+        players
+    }
+}
+
+/// all fantasy points scored in one sim of a slate, keyed like the projections
+pub fn sim_player_points(box_scores: &[BoxScore]) -> HashMap<PlayerKey, PlayerFantasyPoints> {
+    let fps: Vec<FantasyPoints> = box_scores.iter().map(|bs| bs.fantasy_points()).collect();
+    FantasyPoints::combine(&fps).by_player_key()
+}
@@ -0,0 +1,425 @@
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+
+use crate::dfs::{sim_player_points, DfsPlayer, DfsSite};
+use crate::params::GameParamsDistribution;
+use crate::sim::box_score::{BoxScore, PlayerFantasyPoints, PlayerKey};
+use crate::sim::run::sim_box_scores_rayon;
+
+const N_TOP_LINEUPS: usize = 100;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ShowdownRules {
+    pub site: DfsSite,
+    pub salary_cap: u32,
+    pub n_flex: usize,
+    pub captain_points_mult: f32,
+    pub captain_salary_mult: f32,
+}
+
+impl ShowdownRules {
+    pub fn draftkings() -> ShowdownRules {
+        ShowdownRules {
+            site: DfsSite::DraftKings,
+            salary_cap: 50_000,
+            n_flex: 5,
+            captain_points_mult: 1.5,
+            captain_salary_mult: 1.5,
+        }
+    }
+
+    /// FanDuel calls the captain the MVP, and does not charge extra salary for it
+    pub fn fanduel() -> ShowdownRules {
+        ShowdownRules {
+            site: DfsSite::FanDuel,
+            salary_cap: 60_000,
+            n_flex: 4,
+            captain_points_mult: 1.5,
+            captain_salary_mult: 1.0,
+        }
+    }
+
+    pub fn captain_salary(&self, salary: u32) -> u32 {
+        (salary as f32 * self.captain_salary_mult).round() as u32
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShowdownLineup {
+    pub captain: PlayerKey,
+    // sorted, so the same roster always hashes the same
+    pub flex: Vec<PlayerKey>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShowdownPlayerSummary {
+    pub captain_count: u32,
+    pub flex_count: u32,
+    pub mean_points: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShowdownLineupSummary {
+    pub lineup: ShowdownLineup,
+    pub salary: u32,
+    pub optimal_count: u32,
+    pub mean_points: f32,
+    pub std_points: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShowdownResults {
+    pub n_sims: u32,
+    pub players: HashMap<PlayerKey, ShowdownPlayerSummary>,
+    // most frequently optimal lineups, evaluated across every sim
+    pub lineups: Vec<ShowdownLineupSummary>,
+}
+
+pub struct ShowdownOptimizer {
+    pub rules: ShowdownRules,
+    pub players: Vec<DfsPlayer>,
+    pub home: String,
+    pub away: String,
+    // salaries below are in units of the gcd of every salary, so the knapsack stays small
+    flex_cost: Vec<usize>,
+    captain_cost: Vec<usize>,
+    team_bit: Vec<usize>,
+    capacity: usize,
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+impl ShowdownOptimizer {
+    pub fn new(
+        rules: ShowdownRules,
+        players: Vec<DfsPlayer>,
+        home: &str,
+        away: &str,
+    ) -> ShowdownOptimizer {
+        let players: Vec<DfsPlayer> = players
+            .into_iter()
+            .filter(|p| p.team == home || p.team == away)
+            .collect();
+        let unit = players.iter().fold(rules.salary_cap, |acc, p| {
+            gcd(gcd(acc, p.salary), rules.captain_salary(p.salary))
+        });
+        let unit = unit.max(1);
+        ShowdownOptimizer {
+            flex_cost: players.iter().map(|p| (p.salary / unit) as usize).collect(),
+            captain_cost: players
+                .iter()
+                .map(|p| (rules.captain_salary(p.salary) / unit) as usize)
+                .collect(),
+            team_bit: players
+                .iter()
+                .map(|p| if p.team == home { 1 } else { 2 })
+                .collect(),
+            capacity: (rules.salary_cap / unit) as usize,
+            rules,
+            players,
+            home: home.to_string(),
+            away: away.to_string(),
+        }
+    }
+
+    fn state_index(&self, n_flex: usize, has_captain: usize, teams: usize, salary: usize) -> usize {
+        ((n_flex * 2 + has_captain) * 4 + teams) * (self.capacity + 1) + salary
+    }
+
+    fn player_points(&self, points: &HashMap<PlayerKey, PlayerFantasyPoints>) -> Vec<f32> {
+        self.players
+            .iter()
+            .map(|p| match points.get(&p.key) {
+                Some(fp) => self.rules.site.points(fp),
+                None => 0.0,
+            })
+            .collect()
+    }
+
+    /// exact optimum for one sim: a 0/1 knapsack where each player is skipped,
+    /// rostered at flex or rostered at captain, and both teams must be represented
+    pub fn optimal_lineup(
+        &self,
+        points: &HashMap<PlayerKey, PlayerFantasyPoints>,
+    ) -> Option<ShowdownLineup> {
+        let player_points = self.player_points(points);
+        let n_flex = self.rules.n_flex;
+        let n_states = self.state_index(n_flex + 1, 0, 0, 0);
+        let mut best = vec![f32::NEG_INFINITY; n_states];
+        // low 2 bits: 1 = flex, 2 = captain. next 2 bits: team mask before adding the player
+        let mut choices = vec![0u8; n_states * self.players.len()];
+        best[self.state_index(0, 0, 0, 0)] = 0.0;
+
+        for (ii, pts) in player_points.iter().enumerate() {
+            let mut next = best.clone();
+            let layer = &mut choices[ii * n_states..(ii + 1) * n_states];
+            let bit = self.team_bit[ii];
+            for k in 0..=n_flex {
+                for c in 0..2 {
+                    for t in 0..4 {
+                        for s in 0..=self.capacity {
+                            let value = best[self.state_index(k, c, t, s)];
+                            if value == f32::NEG_INFINITY {
+                                continue;
+                            }
+                            let flex_salary = s + self.flex_cost[ii];
+                            if k < n_flex && flex_salary <= self.capacity {
+                                let ns = self.state_index(k + 1, c, t | bit, flex_salary);
+                                if value + pts > next[ns] {
+                                    next[ns] = value + pts;
+                                    layer[ns] = 1 | (t << 2) as u8;
+                                }
+                            }
+                            let captain_salary = s + self.captain_cost[ii];
+                            if c == 0 && captain_salary <= self.capacity {
+                                let ns = self.state_index(k, 1, t | bit, captain_salary);
+                                let captain_value = value + self.rules.captain_points_mult * pts;
+                                if captain_value > next[ns] {
+                                    next[ns] = captain_value;
+                                    layer[ns] = 2 | (t << 2) as u8;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            best = next;
+        }
+
+        let mut state = (0..=self.capacity)
+            .map(|s| self.state_index(n_flex, 1, 3, s))
+            .filter(|ix| best[*ix] > f32::NEG_INFINITY)
+            .max_by(|a, b| best[*a].partial_cmp(&best[*b]).unwrap())?;
+
+        let (mut k, mut s) = (n_flex, state % (self.capacity + 1));
+        let mut captain = None;
+        let mut flex = vec![];
+        for ii in (0..self.players.len()).rev() {
+            let choice = choices[ii * n_states + state];
+            match choice & 3 {
+                1 => {
+                    flex.push(self.players[ii].key.clone());
+                    k -= 1;
+                    s -= self.flex_cost[ii];
+                }
+                2 => {
+                    captain = Some(self.players[ii].key.clone());
+                    s -= self.captain_cost[ii];
+                }
+                _ => continue,
+            }
+            let t = (choice >> 2) as usize;
+            let c = if captain.is_some() { 0 } else { 1 };
+            state = self.state_index(k, c, t, s);
+        }
+        flex.sort_by_key(|key| key.to_string());
+        Some(ShowdownLineup {
+            captain: captain?,
+            flex,
+        })
+    }
+
+    pub fn lineup_points(
+        &self,
+        lineup: &ShowdownLineup,
+        points: &HashMap<PlayerKey, PlayerFantasyPoints>,
+    ) -> f32 {
+        let pts = |key: &PlayerKey| match points.get(key) {
+            Some(fp) => self.rules.site.points(fp),
+            None => 0.0,
+        };
+        let flex_points: f32 = lineup.flex.iter().map(pts).sum();
+        flex_points + self.rules.captain_points_mult * pts(&lineup.captain)
+    }
+
+    pub fn lineup_salary(&self, lineup: &ShowdownLineup) -> u32 {
+        let salary = |key: &PlayerKey| {
+            self.players
+                .iter()
+                .find(|p| &p.key == key)
+                .map(|p| p.salary)
+                .unwrap_or(0)
+        };
+        let flex_salary: u32 = lineup.flex.iter().map(salary).sum();
+        flex_salary + self.rules.captain_salary(salary(&lineup.captain))
+    }
+
+    /// find the optimal lineup in every sim of this game, then evaluate
+    /// the most frequently optimal lineups against every sim
+    pub fn optimize(&self, sims: &[BoxScore]) -> ShowdownResults {
+        let sim_points: Vec<HashMap<PlayerKey, PlayerFantasyPoints>> = sims
+            .iter()
+            .map(|bs| sim_player_points(std::slice::from_ref(bs)))
+            .collect();
+        let optimal: Vec<Option<ShowdownLineup>> = sim_points
+            .par_iter()
+            .map(|points| self.optimal_lineup(points))
+            .collect();
+
+        let n_sims = sims.len() as u32;
+        let mut players: HashMap<PlayerKey, ShowdownPlayerSummary> = HashMap::new();
+        for (ii, player) in self.players.iter().enumerate() {
+            let total: f32 = sim_points
+                .iter()
+                .map(|points| self.player_points(points)[ii])
+                .sum();
+            players.insert(
+                player.key.clone(),
+                ShowdownPlayerSummary {
+                    captain_count: 0,
+                    flex_count: 0,
+                    mean_points: total / n_sims as f32,
+                },
+            );
+        }
+
+        let mut lineup_counts: HashMap<ShowdownLineup, u32> = HashMap::new();
+        for lineup in optimal.into_iter().flatten() {
+            players.get_mut(&lineup.captain).unwrap().captain_count += 1;
+            for key in &lineup.flex {
+                players.get_mut(key).unwrap().flex_count += 1;
+            }
+            *lineup_counts.entry(lineup).or_insert(0) += 1;
+        }
+
+        let mut top_lineups: Vec<(ShowdownLineup, u32)> = lineup_counts.into_iter().collect();
+        top_lineups.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        top_lineups.truncate(N_TOP_LINEUPS);
+
+        let lineups = top_lineups
+            .into_par_iter()
+            .map(|(lineup, optimal_count)| {
+                let scores: Vec<f32> = sim_points
+                    .iter()
+                    .map(|points| self.lineup_points(&lineup, points))
+                    .collect();
+                let mean_points = scores.iter().sum::<f32>() / n_sims as f32;
+                let var = scores
+                    .iter()
+                    .map(|x| (x - mean_points).powi(2))
+                    .sum::<f32>()
+                    / n_sims as f32;
+                ShowdownLineupSummary {
+                    salary: self.lineup_salary(&lineup),
+                    lineup,
+                    optimal_count,
+                    mean_points,
+                    std_points: var.sqrt(),
+                }
+            })
+            .collect();
+
+        ShowdownResults {
+            n_sims,
+            players,
+            lineups,
+        }
+    }
+
+    pub fn write(&self, slate_dir: &str, results: &ShowdownResults) {
+        let proj_path = format!("{}/projections", slate_dir);
+        std::fs::create_dir_all(&proj_path).unwrap();
+        let prefix = format!(
+            "{}/showdown_{}_{}_{}",
+            proj_path, self.rules.site, self.away, self.home
+        );
+        let n_sims = results.n_sims as f32;
+
+        let mut wtr = csv::Writer::from_path(format!("{}_players.csv", prefix)).unwrap();
+        wtr.write_record([
+            "player_key",
+            "name",
+            "team",
+            "salary",
+            "cpt_salary",
+            "mean_points",
+            "optimal%",
+            "cpt%",
+            "flex%",
+            "cpt_share",
+        ])
+        .unwrap();
+        let mut players: Vec<&DfsPlayer> = self.players.iter().collect();
+        players.sort_by(|a, b| {
+            let a_pts = results.players[&a.key].mean_points;
+            let b_pts = results.players[&b.key].mean_points;
+            b_pts.partial_cmp(&a_pts).unwrap()
+        });
+        for player in players {
+            let summary = &results.players[&player.key];
+            let n_optimal = (summary.captain_count + summary.flex_count) as f32;
+            let cpt_share = match n_optimal > 0.0 {
+                true => summary.captain_count as f32 / n_optimal,
+                false => 0.0,
+            };
+            wtr.write_record(&[
+                player.key.to_string(),
+                player.name.clone(),
+                player.team.clone(),
+                player.salary.to_string(),
+                self.rules.captain_salary(player.salary).to_string(),
+                format!("{:.2}", summary.mean_points),
+                format!("{:.1}%", 100.0 * n_optimal / n_sims),
+                format!("{:.1}%", 100.0 * summary.captain_count as f32 / n_sims),
+                format!("{:.1}%", 100.0 * summary.flex_count as f32 / n_sims),
+                format!("{:.2}", cpt_share),
+            ])
+            .unwrap();
+        }
+        wtr.flush().unwrap();
+
+        let mut wtr = csv::Writer::from_path(format!("{}_lineups.csv", prefix)).unwrap();
+        let mut header = vec![String::from("captain")];
+        for ii in 1..=self.rules.n_flex {
+            header.push(format!("flex{}", ii));
+        }
+        for col in ["salary", "mean_points", "std_points", "optimal%"] {
+            header.push(col.to_string());
+        }
+        wtr.write_record(&header).unwrap();
+        for summary in &results.lineups {
+            let mut record = vec![summary.lineup.captain.to_string()];
+            for key in &summary.lineup.flex {
+                record.push(key.to_string());
+            }
+            record.push(summary.salary.to_string());
+            record.push(format!("{:.2}", summary.mean_points));
+            record.push(format!("{:.2}", summary.std_points));
+            record.push(format!(
+                "{:.2}%",
+                100.0 * summary.optimal_count as f32 / n_sims
+            ));
+            wtr.write_record(&record).unwrap();
+        }
+        wtr.flush().unwrap();
+    }
+}
+
+/// sim one game and optimize showdown lineups against its joint outcomes
+pub fn run_showdown(
+    n_sims: u32,
+    game_params: &GameParamsDistribution,
+    rules: ShowdownRules,
+    players: Vec<DfsPlayer>,
+    sim_injuries: bool,
+) -> (ShowdownOptimizer, ShowdownResults) {
+    let optimizer = ShowdownOptimizer::new(
+        rules,
+        players,
+        &game_params.home.team.team,
+        &game_params.away.team.team,
+    );
+    let sims: Vec<BoxScore> =
+        sim_box_scores_rayon(n_sims, &vec![game_params.clone()], sim_injuries, "Showdown")
+            .into_iter()
+            .map(|mut box_scores| box_scores.remove(0))
+            .collect();
+    let results = optimizer.optimize(&sims);
+    (optimizer, results)
+}
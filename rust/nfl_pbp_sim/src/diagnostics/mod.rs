@@ -0,0 +1,152 @@
+pub mod rates;
+
+use std::collections::HashMap;
+
+use csv::Reader;
+use serde::Deserialize;
+
+use crate::{
+    diagnostics::rates::LeagueTotals, params::GameParamsDistribution, sim::box_score::BoxScore,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Baseline {
+    pub metric: String,
+    pub target: f32,
+    // largest absolute miss that still passes
+    pub tolerance: f32,
+}
+
+impl Baseline {
+    /// loads `league_rates.csv` from a baselines folder, keyed by metric
+    pub fn load(baselines_dir: &str) -> HashMap<String, Baseline> {
+        let path = format!("{}/league_rates.csv", baselines_dir);
+        log::info!("loading league baselines from {}", path);
+        let mut reader =
+            Reader::from_path(&path).unwrap_or_else(|_| panic!("Failed to load file at {}", path));
+        let mut baselines = HashMap::new();
+        for row in reader.deserialize() {
+            let baseline: Baseline = row.expect("failed to parse baseline");
+            baselines.insert(baseline.metric.clone(), baseline);
+        }
+        baselines
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateCheck {
+    pub metric: String,
+    pub simulated: f32,
+    // none when the baselines have no target for the metric
+    pub baseline: Option<Baseline>,
+}
+
+impl RateCheck {
+    /// simulated minus target
+    pub fn diff(&self) -> Option<f32> {
+        self.baseline.as_ref().map(|b| self.simulated - b.target)
+    }
+
+    pub fn in_tolerance(&self) -> bool {
+        match (&self.baseline, self.diff()) {
+            (Some(baseline), Some(diff)) => diff.abs() <= baseline.tolerance,
+            _ => true,
+        }
+    }
+
+    pub fn flag(&self) -> &'static str {
+        match self.diff() {
+            None => "",
+            Some(_) if self.in_tolerance() => "ok",
+            Some(diff) if diff > 0.0 => "high",
+            Some(_) => "low",
+        }
+    }
+}
+
+/// every team in the slate against a league average version of its opponent,
+/// once at home and once on the road
+pub fn vs_average_opponent(game_params: &[GameParamsDistribution]) -> Vec<GameParamsDistribution> {
+    game_params
+        .iter()
+        .flat_map(|gp| {
+            let neutral = gp.neutral();
+            [
+                GameParamsDistribution {
+                    home: gp.home.clone(),
+                    away: neutral.away,
+                    weather: gp.weather.clone(),
+                    neutral_field: gp.neutral_field,
+                    market: None,
+                    environment_sd: gp.environment_sd,
+                },
+                GameParamsDistribution {
+                    home: neutral.home,
+                    away: gp.away.clone(),
+                    weather: gp.weather.clone(),
+                    neutral_field: gp.neutral_field,
+                    market: None,
+                    environment_sd: gp.environment_sd,
+                },
+            ]
+        })
+        .collect()
+}
+
+/// compares league-wide simulated rates to the baselines, warning on each miss
+pub fn check_rates(
+    box_scores: &[Vec<BoxScore>],
+    baselines: &HashMap<String, Baseline>,
+) -> Vec<RateCheck> {
+    let mut totals = LeagueTotals::default();
+    for box_score in box_scores.iter().flatten() {
+        totals.add(box_score);
+    }
+    let checks: Vec<RateCheck> = totals
+        .rates()
+        .into_iter()
+        .map(|(metric, simulated)| RateCheck {
+            metric: metric.to_string(),
+            simulated,
+            baseline: baselines.get(metric).cloned(),
+        })
+        .collect();
+
+    for check in checks.iter().filter(|c| !c.in_tolerance()) {
+        let baseline = check.baseline.as_ref().unwrap();
+        log::warn!(
+            "{} is {:.4}, target {:.4} +/- {:.4}",
+            check.metric,
+            check.simulated,
+            baseline.target,
+            baseline.tolerance
+        );
+    }
+    log::info!(
+        "{} of {} rates within tolerance over {} games",
+        checks.iter().filter(|c| c.in_tolerance()).count(),
+        checks.len(),
+        totals.games
+    );
+    checks
+}
+
+pub fn write_diagnostics(out_dir: &str, checks: &[RateCheck]) {
+    std::fs::create_dir_all(out_dir).unwrap();
+    let fmt_opt = |v: Option<f32>| v.map_or("".into(), |v| format!("{:.4}", v));
+    let mut wtr = csv::Writer::from_path(format!("{}/diagnostics.csv", out_dir)).unwrap();
+    wtr.write_record(["metric", "simulated", "target", "tolerance", "diff", "flag"])
+        .unwrap();
+    for check in checks {
+        wtr.write_record(&[
+            check.metric.clone(),
+            format!("{:.4}", check.simulated),
+            fmt_opt(check.baseline.as_ref().map(|b| b.target)),
+            fmt_opt(check.baseline.as_ref().map(|b| b.tolerance)),
+            fmt_opt(check.diff()),
+            check.flag().to_string(),
+        ])
+        .unwrap();
+    }
+    wtr.flush().unwrap();
+}
@@ -0,0 +1,137 @@
+use crate::{
+    box_score::drive::DriveResult,
+    sim::box_score::BoxScore,
+    state::{clock::Quarter, game_state::TeamPlays},
+};
+
+pub const PASS_RATE: &str = "pass_rate";
+pub const SACK_RATE: &str = "sack_rate";
+pub const SCRAMBLE_RATE: &str = "scramble_rate";
+pub const COMPLETION_PCT: &str = "completion_pct";
+pub const INT_RATE: &str = "int_rate";
+pub const YARDS_PER_CARRY: &str = "yards_per_carry";
+pub const PLAYS_PER_TEAM_GAME: &str = "plays_per_team_game";
+pub const PENALTIES_PER_GAME: &str = "penalties_per_game";
+pub const POINTS_PER_GAME: &str = "points_per_game";
+pub const PUNT_RATE: &str = "punt_rate";
+pub const FG_ATTEMPT_RATE: &str = "fg_attempt_rate";
+pub const TWO_POINT_ATTEMPT_RATE: &str = "two_point_attempt_rate";
+pub const ONSIDE_KICK_RATE: &str = "onside_kick_rate";
+pub const OVERTIME_RATE: &str = "overtime_rate";
+
+/// league-wide counts summed over every simulated game
+#[derive(Debug, Clone, Default)]
+pub struct LeagueTotals {
+    pub games: u32,
+    pub plays: u32,
+    pub dropbacks: u32,
+    pub runs: u32,
+    pub sacks: u32,
+    pub scrambles: u32,
+    pub attempts: u32,
+    pub completions: u32,
+    pub interceptions: u32,
+    pub carries: u32,
+    pub rushing_yards: i32,
+    pub penalties: u32,
+    pub points: u32,
+    pub drives: u32,
+    pub punts: u32,
+    pub fg_attempts: u32,
+    // tries after a touchdown, kicked or not
+    pub tries: u32,
+    pub two_point_attempts: u32,
+    pub kickoffs: u32,
+    pub onside_kicks: u32,
+    pub overtime_games: u32,
+}
+
+impl LeagueTotals {
+    pub fn add(&mut self, box_score: &BoxScore) {
+        self.games += 1;
+        for plays in [&box_score.plays.home, &box_score.plays.away] {
+            self.add_plays(plays);
+        }
+        for passing in box_score.passers.values() {
+            self.scrambles += passing.scrambles as u32;
+            self.attempts += passing.attempts as u32;
+            self.completions += passing.completions as u32;
+            self.interceptions += passing.interceptions as u32;
+        }
+        for skill_player in box_score.skill_players.values() {
+            self.carries += skill_player.carries as u32;
+            self.rushing_yards += skill_player.rushing_yards as i32;
+        }
+        for defense in box_score.defenses.values() {
+            self.sacks += defense.sacks as u32;
+            self.penalties += (defense.offensive_penalties + defense.defensive_penalties) as u32;
+        }
+        for kicker in box_score.kickers.values() {
+            self.tries +=
+                (kicker.pats_made + kicker.pats_missed + kicker.two_point_attempts) as u32;
+            self.two_point_attempts += kicker.two_point_attempts as u32;
+            self.kickoffs += kicker.kickoffs as u32;
+            self.onside_kicks += kicker.onside_kicks as u32;
+        }
+        self.points += (box_score.score.home + box_score.score.away) as u32;
+        for drive in &box_score.drives {
+            self.drives += 1;
+            match drive.result {
+                Some(DriveResult::Punt) => self.punts += 1,
+                Some(DriveResult::FieldGoal) | Some(DriveResult::MissedFieldGoal) => {
+                    self.fg_attempts += 1
+                }
+                _ => {}
+            }
+        }
+        if box_score.quarter == Quarter::Overtime {
+            self.overtime_games += 1;
+        }
+    }
+
+    fn add_plays(&mut self, plays: &TeamPlays) {
+        self.plays += plays.total as u32;
+        self.dropbacks += plays.dropbacks as u32;
+        self.runs += plays.run as u32;
+    }
+
+    fn rate(numerator: f32, denominator: u32) -> f32 {
+        match denominator > 0 {
+            true => numerator / denominator as f32,
+            false => 0.0,
+        }
+    }
+
+    /// every tracked rate, by metric name
+    pub fn rates(&self) -> Vec<(&'static str, f32)> {
+        let rate = LeagueTotals::rate;
+        vec![
+            (
+                PASS_RATE,
+                rate(self.dropbacks as f32, self.dropbacks + self.runs),
+            ),
+            (SACK_RATE, rate(self.sacks as f32, self.dropbacks)),
+            (SCRAMBLE_RATE, rate(self.scrambles as f32, self.dropbacks)),
+            (COMPLETION_PCT, rate(self.completions as f32, self.attempts)),
+            (INT_RATE, rate(self.interceptions as f32, self.attempts)),
+            (
+                YARDS_PER_CARRY,
+                rate(self.rushing_yards as f32, self.carries),
+            ),
+            (PLAYS_PER_TEAM_GAME, rate(self.plays as f32, 2 * self.games)),
+            (PENALTIES_PER_GAME, rate(self.penalties as f32, self.games)),
+            (POINTS_PER_GAME, rate(self.points as f32, self.games)),
+            (PUNT_RATE, rate(self.punts as f32, self.drives)),
+            (FG_ATTEMPT_RATE, rate(self.fg_attempts as f32, self.drives)),
+            (
+                TWO_POINT_ATTEMPT_RATE,
+                rate(self.two_point_attempts as f32, self.tries),
+            ),
+            (
+                ONSIDE_KICK_RATE,
+                rate(self.onside_kicks as f32, self.kickoffs),
+            ),
+            (OVERTIME_RATE, rate(self.overtime_games as f32, self.games)),
+        ]
+    }
+}
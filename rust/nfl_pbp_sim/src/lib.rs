@@ -7,8 +7,12 @@ extern crate rust_decimal;
 extern crate serde;
 extern crate serde_json;
 
+pub mod backtest;
 pub mod box_score;
+pub mod dfs;
+pub mod diagnostics;
 pub mod game_loop;
+pub mod markets;
 pub mod models;
 pub mod params;
 pub mod projections;
@@ -0,0 +1,110 @@
+use crate::markets::{
+    center_line, game_label, lines_around, sims_by_game, write_markets, MarketPrice, MarketRow,
+};
+use crate::sim::box_score::BoxScore;
+
+const SPREAD_WIDTH: f32 = 10.0;
+const TOTAL_WIDTH: f32 = 10.0;
+const TEAM_TOTAL_WIDTH: f32 = 7.0;
+// inclusive lower bounds of each winning margin band, the last one is open-ended
+const MARGIN_BANDS: [u8; 6] = [1, 7, 13, 19, 25, 31];
+
+/// price alternate spreads, totals, team totals, winning margins and odd/even
+/// for every game from its simulated final scores
+pub fn price_game_lines(box_scores: &[Vec<BoxScore>]) -> Vec<MarketRow> {
+    sims_by_game(box_scores)
+        .iter()
+        .flat_map(|game_sims| price_game(game_sims))
+        .collect()
+}
+
+pub fn write_game_lines(slate_dir: &str, box_scores: &[Vec<BoxScore>]) {
+    write_markets(slate_dir, "game_markets.csv", &price_game_lines(box_scores));
+}
+
+fn price_game(game_sims: &[&BoxScore]) -> Vec<MarketRow> {
+    let first = game_sims[0];
+    let game = game_label(first);
+    let (home, away) = (first.home.clone(), first.away.clone());
+
+    let home_points: Vec<f32> = game_sims.iter().map(|bs| bs.score.home as f32).collect();
+    let away_points: Vec<f32> = game_sims.iter().map(|bs| bs.score.away as f32).collect();
+    let home_margins: Vec<f32> = home_points
+        .iter()
+        .zip(&away_points)
+        .map(|(h, a)| h - a)
+        .collect();
+    let totals: Vec<f32> = home_points
+        .iter()
+        .zip(&away_points)
+        .map(|(h, a)| h + a)
+        .collect();
+
+    let mut rows = vec![];
+    let row = |market: &str, selection: &str, line: Option<f32>, price: MarketPrice| MarketRow {
+        game: game.clone(),
+        market: market.to_string(),
+        selection: selection.to_string(),
+        line,
+        price,
+    };
+
+    // home -3.5 covers when the home margin is over 3.5
+    let spread_center = -center_line(&mut home_margins.clone());
+    for line in lines_around(spread_center, SPREAD_WIDTH) {
+        let home_price = MarketPrice::over(&home_margins, -line);
+        rows.push(row("spread", &home, Some(line), home_price));
+        rows.push(row("spread", &away, Some(-line), home_price.opposite()));
+    }
+
+    for line in lines_around(center_line(&mut totals.clone()), TOTAL_WIDTH) {
+        let over = MarketPrice::over(&totals, line);
+        rows.push(row("total", "over", Some(line), over));
+        rows.push(row("total", "under", Some(line), over.opposite()));
+    }
+
+    for (team, points) in [(&home, &home_points), (&away, &away_points)] {
+        for line in lines_around(center_line(&mut points.clone()), TEAM_TOTAL_WIDTH) {
+            let over = MarketPrice::over(points, line);
+            rows.push(row(
+                "team_total",
+                &format!("{} over", team),
+                Some(line),
+                over,
+            ));
+            rows.push(row(
+                "team_total",
+                &format!("{} under", team),
+                Some(line),
+                over.opposite(),
+            ));
+        }
+    }
+
+    for (team, sign) in [(&home, 1.0), (&away, -1.0)] {
+        for (ix, low) in MARGIN_BANDS.iter().enumerate() {
+            let low = *low as f32;
+            let (selection, high) = match MARGIN_BANDS.get(ix + 1) {
+                Some(next) => (
+                    format!("{} by {}-{}", team, low, *next - 1),
+                    *next as f32 - 1.0,
+                ),
+                None => (format!("{} by {}+", team, low), f32::INFINITY),
+            };
+            let price = MarketPrice::from_hits(
+                home_margins
+                    .iter()
+                    .map(|m| sign * m >= low && sign * m <= high),
+            );
+            rows.push(row("winning_margin", &selection, None, price));
+        }
+    }
+    let tie = MarketPrice::from_hits(home_margins.iter().map(|m| *m == 0.0));
+    rows.push(row("winning_margin", "tie", None, tie));
+
+    let odd = MarketPrice::from_hits(totals.iter().map(|t| *t as u16 % 2 == 1));
+    rows.push(row("odd_even", "odd", None, odd));
+    rows.push(row("odd_even", "even", None, odd.opposite()));
+
+    rows
+}
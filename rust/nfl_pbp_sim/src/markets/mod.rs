@@ -0,0 +1,148 @@
+pub mod game_lines;
+pub mod parlays;
+pub mod periods;
+pub mod player_stats;
+pub mod props;
+pub mod touchdowns;
+
+use crate::projections::odds::{probability_to_american_odds, probability_to_decimal_odds};
+use crate::sim::box_score::BoxScore;
+
+/// share of sims a bet wins, pushes (stake refunded) and loses
+#[derive(Debug, Clone, Copy)]
+pub struct MarketPrice {
+    pub win: f32,
+    pub push: f32,
+    pub loss: f32,
+}
+
+impl MarketPrice {
+    /// a bet that wins when the value lands above the line and pushes when it lands on it
+    pub fn over<T: Into<f32> + Copy>(values: &[T], line: f32) -> MarketPrice {
+        let (mut wins, mut pushes) = (0, 0);
+        for v in values {
+            let v: f32 = (*v).into();
+            if v > line {
+                wins += 1;
+            } else if v == line {
+                pushes += 1;
+            }
+        }
+        MarketPrice::from_counts(wins, pushes, values.len())
+    }
+
+    pub fn under<T: Into<f32> + Copy>(values: &[T], line: f32) -> MarketPrice {
+        MarketPrice::over(values, line).opposite()
+    }
+
+    /// a yes/no market, which can never push
+    pub fn from_hits(hits: impl Iterator<Item = bool>) -> MarketPrice {
+        let (mut n, mut n_hits) = (0, 0);
+        for hit in hits {
+            n += 1;
+            if hit {
+                n_hits += 1;
+            }
+        }
+        MarketPrice::from_counts(n_hits, 0, n)
+    }
+
+    /// counted in integers and divided once, so the three always sum to 1
+    fn from_counts(wins: usize, pushes: usize, n: usize) -> MarketPrice {
+        let n = n as f32;
+        MarketPrice {
+            win: wins as f32 / n,
+            push: pushes as f32 / n,
+            loss: (n - (wins + pushes) as f32) / n,
+        }
+    }
+
+    pub fn opposite(&self) -> MarketPrice {
+        MarketPrice {
+            win: self.loss,
+            push: self.push,
+            loss: self.win,
+        }
+    }
+
+    /// no-vig probability once pushes are refunded
+    pub fn fair_prob(&self) -> f32 {
+        match self.win + self.loss > 0.0 {
+            true => self.win / (self.win + self.loss),
+            false => 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MarketRow {
+    // AWAY@HOME
+    pub game: String,
+    pub market: String,
+    pub selection: String,
+    pub line: Option<f32>,
+    pub price: MarketPrice,
+}
+
+pub fn game_label(box_score: &BoxScore) -> String {
+    format!("{}@{}", box_score.away, box_score.home)
+}
+
+/// round to the nearest half point
+pub fn center_line(values: &mut [f32]) -> f32 {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    (values[values.len() / 2] * 2.0).round() / 2.0
+}
+
+/// every half-point line within `width` points of `center`
+pub fn lines_around(center: f32, width: f32) -> Vec<f32> {
+    let n_steps = (width * 2.0) as i32;
+    (-n_steps..=n_steps)
+        .map(|step| center + step as f32 * 0.5)
+        .collect()
+}
+
+/// box scores come in indexed by sim then game, we want each game's sims together
+pub fn sims_by_game(box_scores: &[Vec<BoxScore>]) -> Vec<Vec<&BoxScore>> {
+    let n_games = box_scores.first().map(|sim| sim.len()).unwrap_or(0);
+    (0..n_games)
+        .map(|game_ix| box_scores.iter().map(|sim| &sim[game_ix]).collect())
+        .collect()
+}
+
+pub fn write_markets(slate_dir: &str, file_name: &str, rows: &[MarketRow]) {
+    let proj_path = format!("{}/projections", slate_dir);
+    std::fs::create_dir_all(&proj_path).unwrap();
+    let mut wtr = csv::Writer::from_path(format!("{}/{}", proj_path, file_name)).unwrap();
+    wtr.write_record([
+        "game",
+        "market",
+        "selection",
+        "line",
+        "win",
+        "push",
+        "fair_prob",
+        "american",
+        "decimal",
+    ])
+    .unwrap();
+    for row in rows {
+        let fair_prob = row.price.fair_prob();
+        wtr.write_record(&[
+            row.game.clone(),
+            row.market.clone(),
+            row.selection.clone(),
+            // adding zero turns -0.0 into 0.0
+            row.line
+                .map(|l| format!("{:.1}", l + 0.0))
+                .unwrap_or_default(),
+            format!("{:.4}", row.price.win),
+            format!("{:.4}", row.price.push),
+            format!("{:.4}", fair_prob),
+            probability_to_american_odds(fair_prob),
+            probability_to_decimal_odds(fair_prob),
+        ])
+        .unwrap();
+    }
+    wtr.flush().unwrap();
+}
@@ -0,0 +1,233 @@
+use serde::Deserialize;
+
+use crate::markets::{game_label, player_stats::PlayerStat, MarketPrice};
+use crate::projections::odds::{probability_to_american_odds, probability_to_decimal_odds};
+use crate::sim::box_score::BoxScore;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Over,
+    Under,
+}
+
+/// one leg of a parlay, e.g.
+/// `{"type": "player_stat", "player_id": "00-0036264", "stat": "pass_yards", "side": "over", "line": 249.5}`
+/// `{"type": "spread", "team": "GB", "line": -3.5}`
+/// `{"type": "total", "game": "GB@NYG", "side": "under", "line": 41.5}`
+/// `{"type": "anytime_td", "player_id": "00-0034844"}`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ParlayLeg {
+    PlayerStat {
+        player_id: String,
+        stat: PlayerStat,
+        side: Side,
+        line: f32,
+    },
+    Spread {
+        team: String,
+        line: f32,
+    },
+    Total {
+        game: String,
+        side: Side,
+        line: f32,
+    },
+    AnytimeTd {
+        player_id: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegResult {
+    Win,
+    Push,
+    Loss,
+}
+
+fn over_under(value: f32, side: Side, line: f32) -> LegResult {
+    let over = match value.partial_cmp(&line).unwrap() {
+        std::cmp::Ordering::Greater => LegResult::Win,
+        std::cmp::Ordering::Equal => LegResult::Push,
+        std::cmp::Ordering::Less => LegResult::Loss,
+    };
+    match (side, over) {
+        (Side::Under, LegResult::Win) => LegResult::Loss,
+        (Side::Under, LegResult::Loss) => LegResult::Win,
+        _ => over,
+    }
+}
+
+impl ParlayLeg {
+    /// grade the leg against one sim of the slate
+    pub fn grade(&self, sim: &[BoxScore]) -> LegResult {
+        match self {
+            ParlayLeg::PlayerStat {
+                player_id,
+                stat,
+                side,
+                line,
+            } => over_under(stat.sim_value(sim, player_id), *side, *line),
+            ParlayLeg::Spread { team, line } => {
+                let bs = sim
+                    .iter()
+                    .find(|bs| &bs.home == team || &bs.away == team)
+                    .unwrap_or_else(|| panic!("{} is not on the slate", team));
+                let margin = match &bs.home == team {
+                    true => bs.score.home as f32 - bs.score.away as f32,
+                    false => bs.score.away as f32 - bs.score.home as f32,
+                };
+                over_under(margin, Side::Over, -line)
+            }
+            ParlayLeg::Total { game, side, line } => {
+                let bs = sim
+                    .iter()
+                    .find(|bs| &game_label(bs) == game)
+                    .unwrap_or_else(|| panic!("{} is not on the slate", game));
+                over_under((bs.score.home + bs.score.away) as f32, *side, *line)
+            }
+            ParlayLeg::AnytimeTd { player_id } => over_under(
+                PlayerStat::Touchdowns.sim_value(sim, player_id),
+                Side::Over,
+                0.5,
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for ParlayLeg {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let side = |s: &Side| match s {
+            Side::Over => "o",
+            Side::Under => "u",
+        };
+        match self {
+            ParlayLeg::PlayerStat {
+                player_id,
+                stat,
+                side: s,
+                line,
+            } => write!(f, "{} {} {}{:.1}", player_id, stat, side(s), line),
+            ParlayLeg::Spread { team, line } => write!(f, "{} {:+.1}", team, line),
+            ParlayLeg::Total {
+                game,
+                side: s,
+                line,
+            } => write!(f, "{} {}{:.1}", game, side(s), line),
+            ParlayLeg::AnytimeTd { player_id } => write!(f, "{} anytime TD", player_id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Parlay {
+    pub name: String,
+    pub legs: Vec<ParlayLeg>,
+}
+
+impl Parlay {
+    /// a json array of parlays
+    pub fn load(path: &str) -> Vec<Parlay> {
+        log::info!("loading parlays from {}", path);
+        let file =
+            std::fs::File::open(path).unwrap_or_else(|_| panic!("Failed to load file at {}", path));
+        serde_json::from_reader(file).expect("failed to parse parlays")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParlayPrice {
+    pub name: String,
+    pub legs: String,
+    pub joint: MarketPrice,
+    // product of each leg's no-vig probability, as if the legs were independent
+    pub naive_prob: f32,
+}
+
+/// grade every leg in every sim together. a sim where nothing loses but some legs
+/// push counts as a push, as if the whole ticket were voided
+pub fn price_parlay(parlay: &Parlay, box_scores: &[Vec<BoxScore>]) -> ParlayPrice {
+    let n_sims = box_scores.len() as f32;
+    let mut joint = MarketPrice {
+        win: 0.0,
+        push: 0.0,
+        loss: 0.0,
+    };
+    let mut legs = vec![
+        MarketPrice {
+            win: 0.0,
+            push: 0.0,
+            loss: 0.0,
+        };
+        parlay.legs.len()
+    ];
+    for sim in box_scores {
+        let results: Vec<LegResult> = parlay.legs.iter().map(|leg| leg.grade(sim)).collect();
+        for (leg_price, result) in legs.iter_mut().zip(&results) {
+            match result {
+                LegResult::Win => leg_price.win += 1.0 / n_sims,
+                LegResult::Push => leg_price.push += 1.0 / n_sims,
+                LegResult::Loss => leg_price.loss += 1.0 / n_sims,
+            }
+        }
+        if results.contains(&LegResult::Loss) {
+            joint.loss += 1.0 / n_sims;
+        } else if results.contains(&LegResult::Push) {
+            joint.push += 1.0 / n_sims;
+        } else {
+            joint.win += 1.0 / n_sims;
+        }
+    }
+
+    ParlayPrice {
+        name: parlay.name.clone(),
+        legs: parlay
+            .legs
+            .iter()
+            .map(|leg| leg.to_string())
+            .collect::<Vec<String>>()
+            .join(" | "),
+        joint,
+        naive_prob: legs.iter().map(|leg| leg.fair_prob()).product(),
+    }
+}
+
+pub fn write_parlays(slate_dir: &str, parlays: &[Parlay], box_scores: &[Vec<BoxScore>]) {
+    let proj_path = format!("{}/projections", slate_dir);
+    std::fs::create_dir_all(&proj_path).unwrap();
+    let mut wtr = csv::Writer::from_path(format!("{}/parlays.csv", proj_path)).unwrap();
+    wtr.write_record([
+        "name",
+        "legs",
+        "win",
+        "push",
+        "fair_prob",
+        "american",
+        "decimal",
+        "naive_prob",
+        "naive_american",
+        "naive_decimal",
+        "correlation",
+    ])
+    .unwrap();
+    for parlay in parlays {
+        let price = price_parlay(parlay, box_scores);
+        let fair_prob = price.joint.fair_prob();
+        wtr.write_record(&[
+            price.name.clone(),
+            price.legs.clone(),
+            format!("{:.4}", price.joint.win),
+            format!("{:.4}", price.joint.push),
+            format!("{:.4}", fair_prob),
+            probability_to_american_odds(fair_prob),
+            probability_to_decimal_odds(fair_prob),
+            format!("{:.4}", price.naive_prob),
+            probability_to_american_odds(price.naive_prob),
+            probability_to_decimal_odds(price.naive_prob),
+            format!("{:.2}", fair_prob / price.naive_prob),
+        ])
+        .unwrap();
+    }
+    wtr.flush().unwrap();
+}
@@ -0,0 +1,141 @@
+use crate::markets::{
+    center_line, game_label, lines_around, sims_by_game, write_markets, MarketPrice, MarketRow,
+};
+use crate::sim::box_score::BoxScore;
+use crate::start::HomeAway;
+use crate::state::clock::Quarter;
+
+const PERIOD_SPREAD_WIDTH: f32 = 3.5;
+const PERIOD_TOTAL_WIDTH: f32 = 5.0;
+
+// books grade the second half with overtime included
+const PERIODS: [(&str, &[Quarter]); 6] = [
+    ("1H", &[Quarter::First, Quarter::Second]),
+    ("2H", &[Quarter::Third, Quarter::Fourth, Quarter::Overtime]),
+    ("Q1", &[Quarter::First]),
+    ("Q2", &[Quarter::Second]),
+    ("Q3", &[Quarter::Third]),
+    ("Q4", &[Quarter::Fourth]),
+];
+
+/// price half and quarter spreads, totals and moneylines, the highest scoring half
+/// and the first team to score
+pub fn price_period_lines(box_scores: &[Vec<BoxScore>]) -> Vec<MarketRow> {
+    sims_by_game(box_scores)
+        .iter()
+        .flat_map(|game_sims| price_game_periods(game_sims))
+        .collect()
+}
+
+pub fn write_period_lines(slate_dir: &str, box_scores: &[Vec<BoxScore>]) {
+    write_markets(
+        slate_dir,
+        "period_markets.csv",
+        &price_period_lines(box_scores),
+    );
+}
+
+fn price_game_periods(game_sims: &[&BoxScore]) -> Vec<MarketRow> {
+    let first = game_sims[0];
+    let game = game_label(first);
+    let (home, away) = (first.home.clone(), first.away.clone());
+
+    let mut rows = vec![];
+    let row = |market: String, selection: &str, line: Option<f32>, price: MarketPrice| MarketRow {
+        game: game.clone(),
+        market,
+        selection: selection.to_string(),
+        line,
+        price,
+    };
+
+    for (period, quarters) in PERIODS {
+        let scores: Vec<(f32, f32)> = game_sims
+            .iter()
+            .map(|bs| {
+                let score = bs.period_score(quarters);
+                (score.home as f32, score.away as f32)
+            })
+            .collect();
+        let home_margins: Vec<f32> = scores.iter().map(|(h, a)| h - a).collect();
+        let totals: Vec<f32> = scores.iter().map(|(h, a)| h + a).collect();
+
+        // a tied period pushes the moneyline
+        let home_ml = MarketPrice::over(&home_margins, 0.0);
+        rows.push(row(format!("{}_moneyline", period), &home, None, home_ml));
+        rows.push(row(
+            format!("{}_moneyline", period),
+            &away,
+            None,
+            home_ml.opposite(),
+        ));
+
+        let spread_center = -center_line(&mut home_margins.clone());
+        for line in lines_around(spread_center, PERIOD_SPREAD_WIDTH) {
+            let home_price = MarketPrice::over(&home_margins, -line);
+            rows.push(row(
+                format!("{}_spread", period),
+                &home,
+                Some(line),
+                home_price,
+            ));
+            rows.push(row(
+                format!("{}_spread", period),
+                &away,
+                Some(-line),
+                home_price.opposite(),
+            ));
+        }
+
+        for line in lines_around(center_line(&mut totals.clone()), PERIOD_TOTAL_WIDTH) {
+            let over = MarketPrice::over(&totals, line);
+            rows.push(row(format!("{}_total", period), "over", Some(line), over));
+            rows.push(row(
+                format!("{}_total", period),
+                "under",
+                Some(line),
+                over.opposite(),
+            ));
+        }
+    }
+
+    let (first_half, second_half) = (PERIODS[0].1, PERIODS[1].1);
+    let half_totals: Vec<(u8, u8)> = game_sims
+        .iter()
+        .map(|bs| {
+            let (h1, h2) = (bs.period_score(first_half), bs.period_score(second_half));
+            (h1.home + h1.away, h2.home + h2.away)
+        })
+        .collect();
+    for (selection, pick) in [("1H", 1), ("2H", 2), ("tie", 0)] {
+        let price = MarketPrice::from_hits(half_totals.iter().map(|(h1, h2)| {
+            let winner = match h1.cmp(h2) {
+                std::cmp::Ordering::Greater => 1,
+                std::cmp::Ordering::Less => 2,
+                std::cmp::Ordering::Equal => 0,
+            };
+            winner == pick
+        }));
+        rows.push(row(
+            String::from("highest_scoring_half"),
+            selection,
+            None,
+            price,
+        ));
+    }
+
+    for (selection, team) in [
+        (home.as_str(), Some(HomeAway::Home)),
+        (away.as_str(), Some(HomeAway::Away)),
+        ("no score", None),
+    ] {
+        let price = MarketPrice::from_hits(
+            game_sims
+                .iter()
+                .map(|bs| bs.scoring_plays.first().map(|sp| sp.team) == team),
+        );
+        rows.push(row(String::from("first_to_score"), selection, None, price));
+    }
+
+    rows
+}
@@ -0,0 +1,94 @@
+use serde::Deserialize;
+
+use crate::box_score::{passing::PassingBoxScore, skill_player::SkillPlayerBoxScore};
+use crate::sim::box_score::BoxScore;
+
+/// a player box score stat that props and parlay legs can be written on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlayerStat {
+    PassAttempts,
+    Completions,
+    PassYards,
+    PassTds,
+    Interceptions,
+    Carries,
+    RushYards,
+    RushTds,
+    Targets,
+    Receptions,
+    RecYards,
+    RecTds,
+    LongestReception,
+    RushRecYards,
+    PassRushYards,
+    Touchdowns,
+}
+
+impl std::fmt::Display for PlayerStat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            PlayerStat::PassAttempts => "pass_attempts",
+            PlayerStat::Completions => "completions",
+            PlayerStat::PassYards => "pass_yards",
+            PlayerStat::PassTds => "pass_tds",
+            PlayerStat::Interceptions => "interceptions",
+            PlayerStat::Carries => "carries",
+            PlayerStat::RushYards => "rush_yards",
+            PlayerStat::RushTds => "rush_tds",
+            PlayerStat::Targets => "targets",
+            PlayerStat::Receptions => "receptions",
+            PlayerStat::RecYards => "rec_yards",
+            PlayerStat::RecTds => "rec_tds",
+            PlayerStat::LongestReception => "longest_reception",
+            PlayerStat::RushRecYards => "rush_rec_yards",
+            PlayerStat::PassRushYards => "pass_rush_yards",
+            PlayerStat::Touchdowns => "touchdowns",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl PlayerStat {
+    /// none if the player isn't in this game
+    pub fn value(&self, box_score: &BoxScore, player_id: &str) -> Option<f32> {
+        let passer = box_score.passers.get(player_id);
+        let skill = box_score.skill_players.get(player_id);
+        if passer.is_none() && skill.is_none() {
+            return None;
+        }
+        let pass = |f: fn(&PassingBoxScore) -> f32| passer.map(f).unwrap_or(0.0);
+        let sp = |f: fn(&SkillPlayerBoxScore) -> f32| skill.map(f).unwrap_or(0.0);
+        let value =
+            match self {
+                PlayerStat::PassAttempts => pass(|p| p.attempts as f32),
+                PlayerStat::Completions => pass(|p| p.completions as f32),
+                PlayerStat::PassYards => pass(|p| p.yards as f32),
+                PlayerStat::PassTds => pass(|p| p.touchdowns as f32),
+                PlayerStat::Interceptions => pass(|p| p.interceptions as f32),
+                PlayerStat::Carries => sp(|s| s.carries as f32),
+                PlayerStat::RushYards => sp(|s| s.rushing_yards as f32),
+                PlayerStat::RushTds => sp(|s| s.rushing_touchdowns as f32),
+                PlayerStat::Targets => sp(|s| s.targets as f32),
+                PlayerStat::Receptions => sp(|s| s.catches as f32),
+                PlayerStat::RecYards => sp(|s| s.receiving_yards as f32),
+                PlayerStat::RecTds => sp(|s| s.receiving_touchdowns as f32),
+                PlayerStat::LongestReception => sp(|s| s.longest_reception as f32),
+                PlayerStat::RushRecYards => sp(|s| (s.rushing_yards + s.receiving_yards) as f32),
+                PlayerStat::PassRushYards => {
+                    pass(|p| p.yards as f32) + sp(|s| s.rushing_yards as f32)
+                }
+                PlayerStat::Touchdowns => sp(|s| {
+                    (s.rushing_touchdowns + s.receiving_touchdowns + s.return_touchdowns) as f32
+                }),
+            };
+        Some(value)
+    }
+
+    /// look the player up across every game in one sim of the slate
+    pub fn sim_value(&self, sim: &[BoxScore], player_id: &str) -> f32 {
+        sim.iter()
+            .find_map(|bs| self.value(bs, player_id))
+            .unwrap_or(0.0)
+    }
+}
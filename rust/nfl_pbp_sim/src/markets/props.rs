@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+
+use crate::markets::{player_stats::PlayerStat, sims_by_game, MarketPrice};
+use crate::params::{skill_player::Position, PlayerMeta};
+use crate::projections::odds::probability_to_american_odds;
+use crate::sim::box_score::{BoxScore, PlayerKey};
+
+const QUANTILES: [f32; 5] = [0.1, 0.25, 0.5, 0.75, 0.9];
+
+/// lines from `start` to `end` inclusive, `step` apart
+#[derive(Debug, Clone, Copy)]
+pub struct LadderRange {
+    pub start: f32,
+    pub end: f32,
+    pub step: f32,
+}
+
+impl LadderRange {
+    pub fn lines(&self) -> Vec<f32> {
+        let n_steps = ((self.end - self.start) / self.step).round() as u32;
+        (0..=n_steps)
+            .map(|ix| self.start + ix as f32 * self.step)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PropLadderConfig {
+    pub ranges: Vec<(PlayerStat, LadderRange)>,
+}
+
+impl Default for PropLadderConfig {
+    fn default() -> PropLadderConfig {
+        let range = |start: f32, end: f32, step: f32| LadderRange { start, end, step };
+        PropLadderConfig {
+            ranges: vec![
+                (PlayerStat::PassYards, range(124.5, 374.5, 5.0)),
+                (PlayerStat::Completions, range(9.5, 34.5, 1.0)),
+                (PlayerStat::PassAttempts, range(19.5, 49.5, 1.0)),
+                (PlayerStat::Carries, range(0.5, 29.5, 1.0)),
+                (PlayerStat::RushYards, range(4.5, 149.5, 5.0)),
+                (PlayerStat::Receptions, range(0.5, 12.5, 1.0)),
+                (PlayerStat::RecYards, range(4.5, 149.5, 5.0)),
+                (PlayerStat::LongestReception, range(4.5, 59.5, 5.0)),
+                (PlayerStat::RushRecYards, range(9.5, 199.5, 5.0)),
+                (PlayerStat::PassRushYards, range(149.5, 399.5, 5.0)),
+            ],
+        }
+    }
+}
+
+fn is_passing_stat(stat: PlayerStat) -> bool {
+    matches!(
+        stat,
+        PlayerStat::PassYards
+            | PlayerStat::Completions
+            | PlayerStat::PassAttempts
+            | PlayerStat::PassRushYards
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct PropDistribution {
+    pub player_id: String,
+    pub stat: PlayerStat,
+    // sorted
+    pub values: Vec<f32>,
+}
+
+impl PropDistribution {
+    pub fn mean(&self) -> f32 {
+        self.values.iter().sum::<f32>() / self.values.len() as f32
+    }
+
+    pub fn quantile(&self, q: f32) -> f32 {
+        let ix = (q * (self.values.len() - 1) as f32).round() as usize;
+        self.values[ix]
+    }
+
+    pub fn over(&self, line: f32) -> MarketPrice {
+        MarketPrice::over(&self.values, line)
+    }
+}
+
+/// every simulated value of each configured stat for every player, skipping
+/// passing stats for non-quarterbacks and stats a player never records
+pub fn prop_distributions(
+    box_scores: &[Vec<BoxScore>],
+    player_meta: &HashMap<PlayerKey, PlayerMeta>,
+    config: &PropLadderConfig,
+) -> Vec<PropDistribution> {
+    let game_sims = sims_by_game(box_scores);
+    let mut jobs = vec![];
+    for (player_key, meta) in player_meta {
+        if let PlayerKey::NflId(player_id) = player_key {
+            for (stat, _) in &config.ranges {
+                if is_passing_stat(*stat) && meta.pos != Position::Quarterback {
+                    continue;
+                }
+                jobs.push((player_id.clone(), *stat));
+            }
+        }
+    }
+    let mut distributions: Vec<PropDistribution> = jobs
+        .into_par_iter()
+        .filter_map(|(player_id, stat)| {
+            let sims = game_sims
+                .iter()
+                .find(|sims| stat.value(sims[0], &player_id).is_some())?;
+            let mut values: Vec<f32> = sims
+                .iter()
+                .map(|bs| stat.value(bs, &player_id).unwrap())
+                .collect();
+            if values.iter().all(|v| *v == 0.0) {
+                return None;
+            }
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            Some(PropDistribution {
+                player_id,
+                stat,
+                values,
+            })
+        })
+        .collect();
+    distributions.sort_by(|a, b| {
+        a.player_id
+            .cmp(&b.player_id)
+            .then(a.stat.to_string().cmp(&b.stat.to_string()))
+    });
+    distributions
+}
+
+pub fn write_prop_ladders(
+    slate_dir: &str,
+    box_scores: &[Vec<BoxScore>],
+    player_meta: &HashMap<PlayerKey, PlayerMeta>,
+    config: &PropLadderConfig,
+) {
+    let proj_path = format!("{}/projections", slate_dir);
+    std::fs::create_dir_all(&proj_path).unwrap();
+    let distributions = prop_distributions(box_scores, player_meta, config);
+    let ranges: HashMap<PlayerStat, LadderRange> = config.ranges.iter().cloned().collect();
+
+    let mut ladder_wtr = csv::Writer::from_path(format!("{}/prop_ladders.csv", proj_path)).unwrap();
+    ladder_wtr
+        .write_record([
+            "player_id",
+            "name",
+            "team",
+            "stat",
+            "line",
+            "over",
+            "american",
+        ])
+        .unwrap();
+    let mut quantile_wtr =
+        csv::Writer::from_path(format!("{}/prop_quantiles.csv", proj_path)).unwrap();
+    quantile_wtr
+        .write_record([
+            "player_id",
+            "name",
+            "team",
+            "stat",
+            "mean",
+            "p10",
+            "p25",
+            "p50",
+            "p75",
+            "p90",
+        ])
+        .unwrap();
+
+    for dist in distributions {
+        let meta = &player_meta[&PlayerKey::NflId(dist.player_id.clone())];
+        for line in ranges[&dist.stat].lines() {
+            // half-point lines never push
+            let over = dist.over(line).win;
+            ladder_wtr
+                .write_record(&[
+                    dist.player_id.clone(),
+                    meta.name.clone(),
+                    meta.team.clone(),
+                    dist.stat.to_string(),
+                    format!("{:.1}", line),
+                    format!("{:.4}", over),
+                    probability_to_american_odds(over),
+                ])
+                .unwrap();
+        }
+
+        let mut record = vec![
+            dist.player_id.clone(),
+            meta.name.clone(),
+            meta.team.clone(),
+            dist.stat.to_string(),
+            format!("{:.2}", dist.mean()),
+        ];
+        for q in QUANTILES {
+            record.push(format!("{:.0}", dist.quantile(q)));
+        }
+        quantile_wtr.write_record(&record).unwrap();
+    }
+    ladder_wtr.flush().unwrap();
+    quantile_wtr.flush().unwrap();
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use crate::markets::{game_label, sims_by_game};
+use crate::params::PlayerMeta;
+use crate::projections::odds::{probability_to_american_odds, probability_to_decimal_odds};
+use crate::sim::box_score::{BoxScore, PlayerKey, ScoringPlay};
+use crate::start::HomeAway;
+
+const NO_TD: &str = "no TD";
+
+#[derive(Debug, Clone)]
+pub struct TouchdownRow {
+    pub game: String,
+    // first_td, last_td or anytime_td
+    pub market: String,
+    pub selection: String,
+    // empty for D/ST and no TD
+    pub player_id: String,
+    pub fair_prob: f32,
+}
+
+impl TouchdownRow {
+    /// book price after scaling the fair probability up by the margin
+    pub fn offered_prob(&self, vig: f32) -> f32 {
+        (self.fair_prob * (1.0 + vig)).min(0.999)
+    }
+}
+
+/// first, last and anytime TD scorer probabilities for every game. touchdowns without
+/// a credited player go to the scoring team's D/ST, and a game with no touchdown
+/// settles first and last TD as "no TD"
+pub fn price_touchdown_scorers(
+    box_scores: &[Vec<BoxScore>],
+    player_meta: &HashMap<PlayerKey, PlayerMeta>,
+) -> Vec<TouchdownRow> {
+    sims_by_game(box_scores)
+        .iter()
+        .flat_map(|game_sims| price_game_touchdowns(game_sims, player_meta))
+        .collect()
+}
+
+fn selection_key(bs: &BoxScore, scoring_play: &ScoringPlay) -> (String, String) {
+    match &scoring_play.scorer {
+        Some(player_id) => (player_id.clone(), player_id.clone()),
+        None => {
+            let team = match scoring_play.team {
+                HomeAway::Home => &bs.home,
+                HomeAway::Away => &bs.away,
+            };
+            (format!("{} D/ST", team), String::new())
+        }
+    }
+}
+
+fn price_game_touchdowns(
+    game_sims: &[&BoxScore],
+    player_meta: &HashMap<PlayerKey, PlayerMeta>,
+) -> Vec<TouchdownRow> {
+    let game = game_label(game_sims[0]);
+    let n_sims = game_sims.len() as f32;
+
+    // (market, selection) -> (player_id, n_sims hit)
+    let mut counts: HashMap<(&str, String), (String, f32)> = HashMap::new();
+    for bs in game_sims {
+        let touchdowns: Vec<&ScoringPlay> =
+            bs.scoring_plays.iter().filter(|sp| sp.touchdown).collect();
+        let (first, last) = match (touchdowns.first(), touchdowns.last()) {
+            (Some(first), Some(last)) => (selection_key(bs, first), selection_key(bs, last)),
+            _ => (
+                (NO_TD.to_string(), String::new()),
+                (NO_TD.to_string(), String::new()),
+            ),
+        };
+        counts
+            .entry(("first_td", first.0))
+            .or_insert((first.1, 0.0))
+            .1 += 1.0;
+        counts.entry(("last_td", last.0)).or_insert((last.1, 0.0)).1 += 1.0;
+
+        let mut anytime: Vec<(String, String)> =
+            touchdowns.iter().map(|sp| selection_key(bs, sp)).collect();
+        anytime.sort();
+        anytime.dedup();
+        for (selection, player_id) in anytime {
+            counts
+                .entry(("anytime_td", selection))
+                .or_insert((player_id, 0.0))
+                .1 += 1.0;
+        }
+    }
+
+    let mut rows: Vec<TouchdownRow> = counts
+        .into_iter()
+        .map(|((market, selection), (player_id, hits))| {
+            let selection = match player_meta.get(&PlayerKey::NflId(player_id.clone())) {
+                Some(meta) => meta.name.clone(),
+                None => selection,
+            };
+            TouchdownRow {
+                game: game.clone(),
+                market: market.to_string(),
+                selection,
+                player_id,
+                fair_prob: hits / n_sims,
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        a.market
+            .cmp(&b.market)
+            .then(b.fair_prob.partial_cmp(&a.fair_prob).unwrap())
+    });
+    rows
+}
+
+pub fn write_touchdown_scorers(
+    slate_dir: &str,
+    box_scores: &[Vec<BoxScore>],
+    player_meta: &HashMap<PlayerKey, PlayerMeta>,
+    vig: f32,
+) {
+    let proj_path = format!("{}/projections", slate_dir);
+    std::fs::create_dir_all(&proj_path).unwrap();
+    let mut wtr = csv::Writer::from_path(format!("{}/td_scorers.csv", proj_path)).unwrap();
+    wtr.write_record([
+        "game",
+        "market",
+        "selection",
+        "player_id",
+        "fair_prob",
+        "fair_american",
+        "prob",
+        "american",
+        "decimal",
+    ])
+    .unwrap();
+    for row in price_touchdown_scorers(box_scores, player_meta) {
+        let offered = row.offered_prob(vig);
+        wtr.write_record(&[
+            row.game.clone(),
+            row.market.clone(),
+            row.selection.clone(),
+            row.player_id.clone(),
+            format!("{:.4}", row.fair_prob),
+            probability_to_american_odds(row.fair_prob),
+            format!("{:.4}", offered),
+            probability_to_american_odds(offered),
+            probability_to_decimal_odds(offered),
+        ])
+        .unwrap();
+    }
+    wtr.flush().unwrap();
+}
@@ -350,7 +350,7 @@ impl PlaycallFeatures {
             },
             garbage_time_win: garbage_time * ((possession_diff >= 3.0) as i8 as f32),
             garbage_time_loss: garbage_time * ((possession_diff <= -3.0) as i8 as f32),
-            oline_rushing_z: offense.team.oline_rushing_z,
+            oline_rushing_z: offense.team.oline_rushing_z + offense.team.offense_efficiency,
             dline_rushing_z: defense.team.dline_rushing_z,
             offense_pace_z: offense.team.pace_z,
             offense_pass_rush_z: offense.team.offense_pass_rush_z,
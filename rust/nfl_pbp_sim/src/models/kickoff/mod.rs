@@ -49,7 +49,7 @@ impl KickoffModel {
         if KickoffModel::is_onside_kick(&features) {
             return match random_bool(ONSIDE_RECOVER_PROB) {
                 true => KickingResult::OnsideRecovery(YardsToGoal(60)),
-                false => KickingResult::ReturnedForYards(YardsToGoal(40)),
+                false => KickingResult::OnsideNotRecovered(YardsToGoal(40)),
             };
         }
         if KickoffModel::is_out_of_bounds_kick(&features) {
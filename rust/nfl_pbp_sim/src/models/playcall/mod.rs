@@ -4,7 +4,7 @@ use crate::game_loop::field_goals::fg_distance;
 use crate::util::stats::random_sigmoid;
 use crate::{
     models::features::PlaycallFeatures,
-    sim::{play_result::PlaycallResult, GameSim},
+    sim::{decision::Decision, play_result::PlaycallResult, GameSim},
 };
 
 pub struct PlaycallModel {
@@ -101,6 +101,18 @@ impl PlaycallModel {
         let f = PlaycallFeatures::new(sim);
         // log::debug!("{:?}", f);
 
+        match sim.forced_decision {
+            Some(Decision::FieldGoal) => return PlaycallResult::FieldGoalAttempt,
+            Some(Decision::Punt) => return PlaycallResult::Punt,
+            Some(Decision::GoForIt) => {
+                return match PlaycallModel::is_qb_dropback(&f) {
+                    true => PlaycallResult::QbDropback,
+                    false => PlaycallResult::DesignedRush,
+                }
+            }
+            _ => {}
+        }
+
         if PlaycallModel::is_offensive_penalty(&f) {
             return PlaycallResult::OffensivePenalty;
         }
@@ -24,6 +24,8 @@ pub fn compute_conditional_shares<T: Eq + Hash + Debug>(
         for (_, share) in id_shares.iter_mut() {
             *share /= cumsum;
         }
+        // hash map order varies between runs, which would break seeded replays
+        id_shares.sort_by(|a, b| a.0.cmp(&b.0));
         for (pid, cond_share) in id_shares.iter() {
             let rc = non_remaining_contrib.get_mut(pid).unwrap();
             *rc += cond_share * team_prob;
@@ -46,6 +48,7 @@ pub fn compute_conditional_shares<T: Eq + Hash + Debug>(
     for (_, share) in remaining_shares.iter_mut() {
         *share /= cumsum;
     }
+    remaining_shares.sort_by(|a, b| a.0.cmp(&b.0));
     cond_shares.insert(remaining_t, remaining_shares);
 
     // log::info!("cond_shares: {:?}", cond_shares);
@@ -20,6 +20,9 @@ use crate::util::stats::{
 };
 
 const PROB_COMPLETION: f32 = 0.60;
+// completion percentage added per unit of team offense efficiency
+const CPOE_PER_EFFICIENCY: f32 = 0.05;
+const MAX_PROB_COMPLETION: f32 = 0.95;
 const PROB_INTERCEPTION: f32 = 0.025;
 
 #[derive(Debug)]
@@ -605,7 +608,9 @@ impl TargetModel {
         let log_qb_scramble = (qb.scramble_rate + EPSILON).ln();
         let log_qbps = (qb.prob_sack_given_hit + EPSILON).ln();
 
-        let log_qb_comp_prob = (PROB_COMPLETION + qb.cpoe).ln();
+        let qb_comp_prob =
+            PROB_COMPLETION + qb.cpoe + CPOE_PER_EFFICIENCY * offense.team.offense_efficiency;
+        let log_qb_comp_prob = qb_comp_prob.clamp(EPSILON, MAX_PROB_COMPLETION).ln();
         let log_qb_int_prob = (PROB_INTERCEPTION - qb.int_ue).max(EPSILON).ln();
         let log_wr_catch_prob = (PROB_COMPLETION + receiver.prob_catch_oe).ln();
 
@@ -1,7 +1,10 @@
 pub mod coef;
 
 use crate::models::features::PlaycallFeatures;
-use crate::{sim::GameSim, util::stats::random_sigmoid};
+use crate::{
+    sim::{decision::Decision, GameSim},
+    util::stats::random_sigmoid,
+};
 
 pub struct TwoPointAttemptModel {
     intercept: f32,
@@ -31,6 +34,11 @@ pub struct TwoPointAttemptModel {
 
 impl TwoPointAttemptModel {
     pub fn goes_for_2(sim: &GameSim) -> bool {
+        match sim.forced_decision {
+            Some(Decision::GoForTwo) => return true,
+            Some(Decision::KickPat) => return false,
+            _ => {}
+        }
         let coef = TwoPointAttemptModel::coefs();
         let features = TwoPointAttemptModel::features(sim);
         let z = TwoPointAttemptModel::get_z(&coef, &features);
@@ -1,7 +1,13 @@
 use std::collections::HashMap;
 
+use csv::Reader;
+use serde::{Deserialize, Serialize};
+
 use crate::{
-    params::{GameParams, GameParamsDistribution, PlayerMeta},
+    params::{
+        skill_player::MarketShare, GameParams, GameParamsDistribution, PlayerMeta,
+        TeamParamsDistribution,
+    },
     sim::{
         box_score::{BoxScore, PlayerKey},
         run::sim_box_scores_rayon,
@@ -9,23 +15,305 @@ use crate::{
     state::game_state::TeamPlays,
 };
 
+// a share can't be scaled up past this, unless it started higher
+const MAX_MARKET_SHARE: f32 = 0.95;
+// 64-bit FNV-1a, so cache keys don't change with the Rust release like std's hasher
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+#[derive(Debug, Clone, Copy)]
+pub struct BurnInConfig {
+    pub n_sims: u32,
+    pub max_iterations: u32,
+    // how far a realised carry or target share may sit from its input
+    pub tolerance: f32,
+}
+
+impl Default for BurnInConfig {
+    fn default() -> BurnInConfig {
+        BurnInConfig {
+            n_sims: 5_000,
+            max_iterations: 6,
+            tolerance: 0.005,
+        }
+    }
+}
+
+/// a player's share of the team's designed carries and targets
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlayerShares {
+    pub carries: f32,
+    pub targets: f32,
+}
+
+/// sim the slate and recalibrate the team FP params and every player's carry
+/// and target shares, until the realised shares match the inputs
 pub fn burn_in_params(
-    n: u32,
-    game_params_vec: &Vec<GameParamsDistribution>,
+    game_params_vec: &[GameParamsDistribution],
     player_meta: &HashMap<PlayerKey, PlayerMeta>,
+    config: &BurnInConfig,
 ) -> Vec<GameParamsDistribution> {
-    // let burn_in_box_scores = sim_many(N_BURN_IN_FP, &game_params_vec);
-    let burn_in_box_scores = sim_box_scores_rayon(n, &game_params_vec, false, "FP burn-in");
-    let team_fp_params = accumulate_team_fp_params(&burn_in_box_scores);
-    // log::info!("Team FP params:\n{:#?}", &team_fp_params);
-    let game_params_fp: Vec<GameParamsDistribution> =
-        GameParams::update_fp_params(&game_params_vec, &team_fp_params);
+    let input_shares = input_shares(game_params_vec);
+    let mut game_params_vec = game_params_vec.to_vec();
+
+    for iteration in 1..=config.max_iterations {
+        let sims = sim_box_scores_rayon(
+            config.n_sims,
+            &game_params_vec,
+            false,
+            &format!("Burn-in {}", iteration),
+        );
+        let team_fp_params = accumulate_team_fp_params(&sims);
+        game_params_vec = GameParams::update_fp_params(&game_params_vec, &team_fp_params);
 
-    let burn_in_ms_tgt = sim_box_scores_rayon(n, &game_params_fp, false, "MST burn-in");
-    let mst_updates = accumulate_ms_targets(&burn_in_ms_tgt, player_meta);
-    let game_params_mst = GameParams::update_ms_targets(&game_params_vec, &mst_updates);
+        let realized_shares = accumulate_shares(&sims, player_meta);
+        let max_error = input_shares
+            .iter()
+            .map(|(player_id, input)| {
+                let realized = realized_shares.get(player_id).copied().unwrap_or_default();
+                (realized.carries - input.carries)
+                    .abs()
+                    .max((realized.targets - input.targets).abs())
+            })
+            .fold(0.0, f32::max);
+        log::info!(
+            "burn-in iteration {}: shares off by up to {:.4}",
+            iteration,
+            max_error
+        );
+        if max_error <= config.tolerance {
+            log::info!("burn-in converged in {} iterations", iteration);
+            return game_params_vec;
+        }
+        if iteration < config.max_iterations {
+            rescale_shares(&mut game_params_vec, &input_shares, &realized_shares);
+        }
+    }
+    log::warn!(
+        "burn-in did not converge in {} iterations",
+        config.max_iterations
+    );
+    game_params_vec
+}
+
+/// `burn_in_params`, but reusing the last burn-in run on the same input tables
+/// (or slate file) and config, which is stored under `cache_dir`
+pub fn cached_burn_in_params(
+    params_path: &str,
+    cache_dir: &str,
+    game_params_vec: &[GameParamsDistribution],
+    player_meta: &HashMap<PlayerKey, PlayerMeta>,
+    config: &BurnInConfig,
+) -> Vec<GameParamsDistribution> {
+    let path = format!(
+        "{}/{:016x}",
+        cache_dir,
+        input_hash(params_path, game_params_vec, config)
+    );
+    match std::path::Path::new(&path).exists() {
+        true => {
+            log::info!("reusing burn-in from {}", path);
+            load_burn_in(&path, game_params_vec)
+        }
+        false => {
+            let burned_in = burn_in_params(game_params_vec, player_meta, config);
+            write_burn_in(&path, &burned_in);
+            burned_in
+        }
+    }
+}
+
+/// hash of every file in the params folder, so an edit to any table reruns the
+/// burn-in, along with the settings applied after loading and the crate version
+fn input_hash(
+    params_path: &str,
+    game_params_vec: &[GameParamsDistribution],
+    config: &BurnInConfig,
+) -> u64 {
+    let mut paths: Vec<std::path::PathBuf> = match std::path::Path::new(params_path).is_file() {
+        true => vec![params_path.into()],
+        false => std::fs::read_dir(params_path)
+            .unwrap_or_else(|_| panic!("Failed to read params at {}", params_path))
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.is_file())
+            .collect(),
+    };
+    paths.sort();
+
+    let mut hash = FNV_OFFSET_BASIS;
+    for path in paths {
+        let file_name = path.file_name().unwrap().to_string_lossy();
+        hash = fnv1a(hash, file_name.as_bytes());
+        hash = fnv1a(hash, &std::fs::read(&path).unwrap());
+    }
+    for gp in game_params_vec {
+        for team in [&gp.home, &gp.away] {
+            hash = fnv1a(hash, format!("{:?}", team.share_sampling).as_bytes());
+        }
+    }
+    hash = fnv1a(hash, format!("{:?}", config).as_bytes());
+    fnv1a(hash, env!("CARGO_PKG_VERSION").as_bytes())
+}
+
+/// each part's length goes in after it, so no two splits of the same bytes collide
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .chain(&(bytes.len() as u64).to_le_bytes())
+        .fold(hash, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(FNV_PRIME)
+        })
+}
 
-    game_params_mst
+#[derive(Debug, Serialize, Deserialize)]
+struct BurnInTeam {
+    team: String,
+    prob_rz_given_target: f32,
+    prob_1ytg_given_carry: f32,
+    prob_gz_given_carry: f32,
+}
+
+// same columns as the skill players table
+#[derive(Debug, Serialize, Deserialize)]
+struct BurnInPlayer {
+    player_id: String,
+    ms_carries: f32,
+    msc_std: Option<f32>,
+    ms_targets: f32,
+    mst_std: Option<f32>,
+}
+
+fn teams(game_params_vec: &[GameParamsDistribution]) -> Vec<&TeamParamsDistribution> {
+    game_params_vec
+        .iter()
+        .flat_map(|gp| [&gp.home, &gp.away])
+        .collect()
+}
+
+fn write_burn_in(path: &str, game_params_vec: &[GameParamsDistribution]) {
+    std::fs::create_dir_all(path).unwrap();
+    let mut team_wtr = csv::Writer::from_path(format!("{}/teams.csv", path)).unwrap();
+    let mut player_wtr = csv::Writer::from_path(format!("{}/players.csv", path)).unwrap();
+    for tp in teams(game_params_vec) {
+        team_wtr
+            .serialize(BurnInTeam {
+                team: tp.team.team.clone(),
+                prob_rz_given_target: tp.team.prob_rz_given_target,
+                prob_1ytg_given_carry: tp.team.prob_1ytg_given_carry,
+                prob_gz_given_carry: tp.team.prob_gz_given_carry,
+            })
+            .unwrap();
+        let mut player_ids: Vec<&String> = tp.skill_players.keys().collect();
+        player_ids.sort();
+        for player_id in player_ids {
+            let sp = &tp.skill_players[player_id];
+            player_wtr
+                .serialize(BurnInPlayer {
+                    player_id: player_id.clone(),
+                    ms_carries: sp.ms_carries.mean(),
+                    msc_std: sp.ms_carries.std(),
+                    ms_targets: sp.ms_targets.mean(),
+                    mst_std: sp.ms_targets.std(),
+                })
+                .unwrap();
+        }
+    }
+    team_wtr.flush().unwrap();
+    player_wtr.flush().unwrap();
+    log::info!("saved burn-in to {}", path);
+}
+
+fn load_burn_in(
+    path: &str,
+    game_params_vec: &[GameParamsDistribution],
+) -> Vec<GameParamsDistribution> {
+    let teams_path = format!("{}/teams.csv", path);
+    let team_fp_params: HashMap<String, TeamFpParams> = Reader::from_path(&teams_path)
+        .unwrap_or_else(|_| panic!("Failed to load file at {}", teams_path))
+        .deserialize()
+        .map(|row| {
+            let row: BurnInTeam = row.expect("failed to parse burn-in team");
+            let fp_params = TeamFpParams {
+                prob_rz_given_target: row.prob_rz_given_target,
+                prob_1ytg_given_carry: row.prob_1ytg_given_carry,
+                prob_gz_given_carry: row.prob_gz_given_carry,
+            };
+            (row.team, fp_params)
+        })
+        .collect();
+    let players_path = format!("{}/players.csv", path);
+    let players: HashMap<String, BurnInPlayer> = Reader::from_path(&players_path)
+        .unwrap_or_else(|_| panic!("Failed to load file at {}", players_path))
+        .deserialize()
+        .map(|row| {
+            let row: BurnInPlayer = row.expect("failed to parse burn-in player");
+            (row.player_id.clone(), row)
+        })
+        .collect();
+
+    let mut game_params_vec = GameParams::update_fp_params(game_params_vec, &team_fp_params);
+    for gp in game_params_vec.iter_mut() {
+        for tp in [&mut gp.home, &mut gp.away] {
+            for (player_id, sp) in tp.skill_players.iter_mut() {
+                if let Some(row) = players.get(player_id) {
+                    sp.ms_carries = MarketShare::new(row.ms_carries, row.msc_std);
+                    sp.ms_targets = MarketShare::new(row.ms_targets, row.mst_std);
+                }
+            }
+        }
+    }
+    game_params_vec
+}
+
+/// the shares the sim should realise. inputs needn't sum to one, so they're
+/// normalised within each team
+fn input_shares(game_params_vec: &[GameParamsDistribution]) -> HashMap<String, PlayerShares> {
+    let mut input_shares = HashMap::new();
+    for tp in teams(game_params_vec) {
+        let players = tp.skill_players.values();
+        let total_carries: f32 = players.clone().map(|sp| sp.ms_carries.mean()).sum();
+        let total_targets: f32 = players.clone().map(|sp| sp.ms_targets.mean()).sum();
+        for sp in players {
+            let shares = PlayerShares {
+                carries: match total_carries > 0.0 {
+                    true => sp.ms_carries.mean() / total_carries,
+                    false => 0.0,
+                },
+                targets: match total_targets > 0.0 {
+                    true => sp.ms_targets.mean() / total_targets,
+                    false => 0.0,
+                },
+            };
+            input_shares.insert(sp.player_id.clone(), shares);
+        }
+    }
+    input_shares
+}
+
+/// scale each share by how far the sim was off, leaving players the sim never
+/// gave the ball alone
+fn rescale_shares(
+    game_params_vec: &mut [GameParamsDistribution],
+    input_shares: &HashMap<String, PlayerShares>,
+    realized_shares: &HashMap<String, PlayerShares>,
+) {
+    let rescaled = |share: &MarketShare, input: f32, realized: f32| match realized > 0.0 {
+        true => share.scaled(input / realized, MAX_MARKET_SHARE.max(share.mean())),
+        false => share.clone(),
+    };
+    for gp in game_params_vec.iter_mut() {
+        for tp in [&mut gp.home, &mut gp.away] {
+            for (player_id, sp) in tp.skill_players.iter_mut() {
+                let (input, realized) =
+                    match (input_shares.get(player_id), realized_shares.get(player_id)) {
+                        (Some(input), Some(realized)) => (input, realized),
+                        _ => continue,
+                    };
+                sp.ms_carries = rescaled(&sp.ms_carries, input.carries, realized.carries);
+                sp.ms_targets = rescaled(&sp.ms_targets, input.targets, realized.targets);
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -104,39 +392,47 @@ fn accumulate_team_fp_params(sims: &Vec<Vec<BoxScore>>) -> HashMap<String, TeamF
     team_fp_params
 }
 
-fn accumulate_ms_targets(
+/// realised shares, where a QB's scrambles don't count as designed carries
+fn accumulate_shares(
     sims: &Vec<Vec<BoxScore>>,
     player_meta: &HashMap<PlayerKey, PlayerMeta>,
-) -> HashMap<String, f32> {
-    let mut target_counts = HashMap::new();
-    let mut team_target_counts = HashMap::new();
+) -> HashMap<String, PlayerShares> {
+    let mut player_counts: HashMap<String, PlayerShares> = HashMap::new();
+    let mut team_counts: HashMap<String, PlayerShares> = HashMap::new();
     for box_scores in sims {
         for box_score in box_scores {
             for (player_id, spb) in &box_score.skill_players {
-                if !target_counts.contains_key(player_id) {
-                    target_counts.insert(player_id.clone(), 0.0);
-                }
-                let player_tgts = target_counts.get_mut(player_id).unwrap();
-                *player_tgts += spb.targets as f32;
+                let scrambles = box_score.passers.get(player_id).map_or(0, |p| p.scrambles);
+                let carries = spb.carries.saturating_sub(scrambles) as f32;
+                let targets = spb.targets as f32;
+
+                let player = player_counts.entry(player_id.clone()).or_default();
+                player.carries += carries;
+                player.targets += targets;
 
                 let team = &player_meta[&PlayerKey::NflId(player_id.clone())].team;
-                if !team_target_counts.contains_key(team) {
-                    team_target_counts.insert(team.clone(), 0.0);
-                }
-                let team_tgts = team_target_counts.get_mut(team).unwrap();
-                *team_tgts += spb.targets as f32;
+                let team = team_counts.entry(team.clone()).or_default();
+                team.carries += carries;
+                team.targets += targets;
             }
         }
     }
-    // log::info!("N sims = {}", sims.len());
-    // log::info!("Player targets:\n{:#?}", target_counts);
-    // log::info!("Team target counts:\n{:#?}", team_target_counts);
-
-    let mut ms_targets = HashMap::new();
-    for (player_id, target_count) in target_counts {
-        let team = &player_meta[&PlayerKey::NflId(player_id.clone())].team;
-        ms_targets.insert(player_id.clone(), target_count / team_target_counts[team]);
-    }
-    // log::info!("Ms targets:\n{:#?}", ms_targets);
-    ms_targets
+
+    player_counts
+        .into_iter()
+        .map(|(player_id, counts)| {
+            let team = &team_counts[&player_meta[&PlayerKey::NflId(player_id.clone())].team];
+            let shares = PlayerShares {
+                carries: match team.carries > 0.0 {
+                    true => counts.carries / team.carries,
+                    false => 0.0,
+                },
+                targets: match team.targets > 0.0 {
+                    true => counts.targets / team.targets,
+                    false => 0.0,
+                },
+            };
+            (player_id, shares)
+        })
+        .collect()
 }
@@ -0,0 +1,95 @@
+use crate::{
+    params::{GameParamsDistribution, TeamParamsDistribution},
+    sim::{box_score::BoxScore, run::sim_box_scores_rayon},
+};
+
+// roughly how many points a team scores per unit of offense efficiency
+const POINTS_PER_EFFICIENCY: f32 = 2.0;
+const MAX_EFFICIENCY: f32 = 5.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CalibrationConfig {
+    pub n_sims: u32,
+    pub max_iterations: u32,
+    // points of mean margin and mean total we're happy to be off by
+    pub tolerance: f32,
+}
+
+impl Default for CalibrationConfig {
+    fn default() -> CalibrationConfig {
+        CalibrationConfig {
+            n_sims: 2_000,
+            max_iterations: 8,
+            tolerance: 0.5,
+        }
+    }
+}
+
+/// nudge each team's offense efficiency until the simulated mean margin and total
+/// of every game with a market line lands within tolerance of the book
+pub fn calibrate_to_market(
+    game_params_vec: &[GameParamsDistribution],
+    config: &CalibrationConfig,
+) -> Vec<GameParamsDistribution> {
+    let mut game_params_vec = game_params_vec.to_vec();
+    if game_params_vec.iter().all(|gp| gp.market.is_none()) {
+        log::warn!("no spreads or totals to calibrate to");
+        return game_params_vec;
+    }
+
+    for iteration in 1..=config.max_iterations {
+        let sims = sim_box_scores_rayon(
+            config.n_sims,
+            &game_params_vec,
+            false,
+            &format!("Calibration {}", iteration),
+        );
+        let mut converged = true;
+        for (game_ix, gp) in game_params_vec.iter_mut().enumerate() {
+            let market = match gp.market {
+                Some(market) => market,
+                None => continue,
+            };
+            let (home_points, away_points) = mean_points(&sims, game_ix);
+            let margin_error = (home_points - away_points) + market.spread;
+            let total_error = (home_points + away_points) - market.total;
+            log::info!(
+                "{} @ {}: margin off by {:.2}, total off by {:.2}",
+                gp.away.team.team,
+                gp.home.team.team,
+                margin_error,
+                total_error
+            );
+            if margin_error.abs() <= config.tolerance && total_error.abs() <= config.tolerance {
+                continue;
+            }
+            converged = false;
+            nudge_efficiency(&mut gp.home, market.home_points() - home_points);
+            nudge_efficiency(&mut gp.away, market.away_points() - away_points);
+        }
+        if converged {
+            log::info!("calibrated to the market in {} iterations", iteration);
+            return game_params_vec;
+        }
+    }
+    log::warn!(
+        "calibration did not converge in {} iterations",
+        config.max_iterations
+    );
+    game_params_vec
+}
+
+fn nudge_efficiency(team: &mut TeamParamsDistribution, points_short: f32) {
+    let efficiency = team.team.offense_efficiency + points_short / POINTS_PER_EFFICIENCY;
+    team.team.offense_efficiency = efficiency.clamp(-MAX_EFFICIENCY, MAX_EFFICIENCY);
+}
+
+fn mean_points(sims: &[Vec<BoxScore>], game_ix: usize) -> (f32, f32) {
+    let n = sims.len() as f32;
+    let (mut home, mut away) = (0.0, 0.0);
+    for sim in sims {
+        home += sim[game_ix].score.home as f32 / n;
+        away += sim[game_ix].score.away as f32 / n;
+    }
+    (home, away)
+}
@@ -1,7 +1,11 @@
 pub mod burn_in;
+pub mod calibration;
 pub mod injury;
+pub mod prop_calibration;
 pub mod quarterback;
 pub mod skill_player;
+pub mod slate_builder;
+pub mod slate_file;
 pub mod team;
 pub mod weather;
 
@@ -11,17 +15,20 @@ use serde::Deserialize;
 use std::collections::HashMap;
 
 use crate::params::injury::Injury;
-use crate::params::skill_player::{Position, SkillPlayerDistribution};
+use crate::params::skill_player::{MarketShare, Position, ShareSampling, SkillPlayerDistribution};
 use crate::params::weather::StadiumType;
 use crate::params::{
     quarterback::Quarterback, skill_player::SkillPlayer, team::Team, weather::Weather,
 };
 use crate::sim::box_score::PlayerKey;
 use crate::start::HomeAway;
+use crate::util::stats::random_normal;
 
 use self::burn_in::TeamFpParams;
 
 const MAX_QB2_MS_RUSH: f32 = 0.05;
+// sampled ratings are cut off this many standard deviations out
+const MAX_RATING_SDS: f32 = 3.0;
 
 lazy_static! {
     static ref MAX_INJURIES_PER_POS: HashMap<Position, u8> = {
@@ -48,22 +55,87 @@ pub struct TeamParamsDistribution {
     pub team: Team,
     pub qbs: Vec<Quarterback>,
     pub skill_players: HashMap<String, SkillPlayerDistribution>,
+    pub share_sampling: ShareSampling,
 }
 
 impl TeamParamsDistribution {
     pub fn to_team_params(&self) -> TeamParams {
-        TeamParams {
-            team: self.team.clone(),
-            qbs: self.qbs.clone(),
-            skill_players: self
-                .skill_players
-                .iter()
-                .map(|(k, v)| (k.clone(), v.to_skill_player()))
+        // sample in a fixed order so a seeded sim replays exactly
+        let mut player_ids: Vec<&String> = self.skill_players.keys().collect();
+        player_ids.sort();
+        let skill_players = match self.share_sampling {
+            ShareSampling::Independent => player_ids
+                .into_iter()
+                .map(|k| (k.clone(), self.skill_players[k].to_skill_player()))
                 .collect(),
+            ShareSampling::Joint => {
+                let players: Vec<&SkillPlayerDistribution> =
+                    player_ids.iter().map(|k| &self.skill_players[*k]).collect();
+                let ms_carries = MarketShare::collapse_joint(
+                    &players.iter().map(|sp| &sp.ms_carries).collect::<Vec<_>>(),
+                );
+                let ms_targets = MarketShare::collapse_joint(
+                    &players.iter().map(|sp| &sp.ms_targets).collect::<Vec<_>>(),
+                );
+                players
+                    .iter()
+                    .zip(ms_carries.into_iter().zip(ms_targets))
+                    .map(|(sp, (ms_carries, ms_targets))| {
+                        (
+                            sp.player_id.clone(),
+                            sp.to_skill_player_with_shares(ms_carries, ms_targets),
+                        )
+                    })
+                    .collect()
+            }
+        };
+        TeamParams {
+            team: self.team.sample(),
+            qbs: self.qbs.iter().map(|qb| qb.sample()).collect(),
+            skill_players,
             injuries: HashMap::new(),
         }
     }
 
+    /// same roster, but with an average team and average players
+    pub fn neutral(&self) -> TeamParamsDistribution {
+        let qbs = self
+            .qbs
+            .iter()
+            .map(|qb| {
+                let mut qb = qb.clone();
+                qb.cpoe = 0.0;
+                qb.int_ue = 0.0;
+                qb.ayoe = 0.0;
+                qb.syoe = 0.0;
+                qb.cpoe_sd = 0.0;
+                qb.int_ue_sd = 0.0;
+                qb.ayoe_sd = 0.0;
+                qb
+            })
+            .collect();
+        let skill_players = self
+            .skill_players
+            .iter()
+            .map(|(player_id, sp)| {
+                let mut sp = sp.clone();
+                sp.ryoe = 0.0;
+                sp.prob_catch_oe = 0.0;
+                sp.yac_oe = 0.0;
+                sp.ryoe_sd = 0.0;
+                sp.prob_catch_oe_sd = 0.0;
+                sp.yac_oe_sd = 0.0;
+                (player_id.clone(), sp)
+            })
+            .collect();
+        TeamParamsDistribution {
+            team: self.team.neutral(),
+            qbs,
+            skill_players,
+            share_sampling: self.share_sampling,
+        }
+    }
+
     pub fn update_fp_params(&self, fp_params: &TeamFpParams) -> TeamParamsDistribution {
         let tm = &self.team;
         TeamParamsDistribution {
@@ -87,28 +159,15 @@ impl TeamParamsDistribution {
                 defense_penalty_z: tm.defense_penalty_z,
                 kickoff_returner_id: tm.kickoff_returner_id.clone(),
                 punt_returner_id: tm.punt_returner_id.clone(),
+                offense_efficiency: tm.offense_efficiency,
+                ratings_sd: tm.ratings_sd.clone(),
                 prob_1ytg_given_carry: fp_params.prob_1ytg_given_carry,
                 prob_gz_given_carry: fp_params.prob_gz_given_carry,
                 prob_rz_given_target: fp_params.prob_rz_given_target,
             },
             qbs: self.qbs.clone(),
             skill_players: self.skill_players.clone(),
-        }
-    }
-
-    pub fn update_ms_targets(
-        &self,
-        realized_ms_targets: &HashMap<String, f32>,
-    ) -> TeamParamsDistribution {
-        let mut skill_players = HashMap::new();
-        for (player_id, sp) in &self.skill_players {
-            let new_sp = sp.update_ms_targets(realized_ms_targets[player_id]);
-            skill_players.insert(player_id.clone(), new_sp);
-        }
-        TeamParamsDistribution {
-            team: self.team.clone(),
-            qbs: self.qbs.clone(),
-            skill_players,
+            share_sampling: self.share_sampling,
         }
     }
 }
@@ -452,13 +511,52 @@ pub struct GameParamsDistribution {
     pub away: TeamParamsDistribution,
     pub weather: Weather,
     pub neutral_field: bool,
+    pub market: Option<MarketLine>,
+    // spread of the shared game environment shock, zero for none
+    pub environment_sd: f32,
+}
+
+/// closing lines from the book, with the spread from the home team's side
+#[derive(Clone, Copy, Debug)]
+pub struct MarketLine {
+    pub spread: f32,
+    pub total: f32,
+}
+
+impl MarketLine {
+    pub fn home_points(&self) -> f32 {
+        (self.total - self.spread) / 2.0
+    }
+
+    pub fn away_points(&self) -> f32 {
+        (self.total + self.spread) / 2.0
+    }
 }
 
 impl GameParamsDistribution {
+    /// both teams made league average and played on a neutral field,
+    /// so only the game state separates them
+    pub fn neutral(&self) -> GameParamsDistribution {
+        GameParamsDistribution {
+            home: self.home.neutral(),
+            away: self.away.neutral(),
+            weather: self.weather.clone(),
+            neutral_field: true,
+            market: None,
+            environment_sd: self.environment_sd,
+        }
+    }
+
     pub fn to_game_params(&self) -> GameParams {
+        // one draw for the whole game, e.g. a shootout or a sloppy defensive struggle
+        let environment = sample_rating(0.0, self.environment_sd);
+        let mut home = self.home.to_team_params();
+        let mut away = self.away.to_team_params();
+        home.team.shift_environment(environment);
+        away.team.shift_environment(environment);
         GameParams {
-            home: self.home.to_team_params(),
-            away: self.away.to_team_params(),
+            home,
+            away,
             weather: self.weather.clone(),
             neutral_field: self.neutral_field,
             sim_injuries: true,
@@ -482,6 +580,11 @@ pub struct GameLoader {
     pub time: String,
     pub stadium_type: StadiumType,
     pub neutral_field: Option<u8>,
+    // home spread, e.g. -3.5 when the home team is favored
+    pub spread: Option<f32>,
+    pub total: Option<f32>,
+    // sd of the game environment shock, in units of `Team::shift_environment`
+    pub environment_sd: Option<f32>,
 }
 
 impl GameLoader {
@@ -544,11 +647,13 @@ impl GameParams {
                     team: teams[&g.home].clone(),
                     qbs: qbs[&g.home].clone(),
                     skill_players: skill_players[&g.home].clone(),
+                    share_sampling: ShareSampling::Independent,
                 },
                 away: TeamParamsDistribution {
                     team: teams[&g.away].clone(),
                     qbs: qbs[&g.away].clone(),
                     skill_players: skill_players[&g.away].clone(),
+                    share_sampling: ShareSampling::Independent,
                 },
                 weather: Weather {
                     stadium_type: g.stadium_type.clone(),
@@ -559,6 +664,11 @@ impl GameParams {
                     Some(0) => false,
                     _ => true,
                 },
+                market: match (g.spread, g.total) {
+                    (Some(spread), Some(total)) => Some(MarketLine { spread, total }),
+                    _ => None,
+                },
+                environment_sd: g.environment_sd.unwrap_or(0.0),
             })
             .collect()
     }
@@ -697,7 +807,7 @@ impl GameParams {
     }
 
     pub fn update_fp_params(
-        gp_dist: &Vec<GameParamsDistribution>,
+        gp_dist: &[GameParamsDistribution],
         team_fp_params: &HashMap<String, TeamFpParams>,
     ) -> Vec<GameParamsDistribution> {
         gp_dist
@@ -712,27 +822,38 @@ impl GameParams {
                     .update_fp_params(&team_fp_params[&gp.away.team.team]),
                 weather: gp.weather,
                 neutral_field: gp.neutral_field,
+                market: gp.market,
+                environment_sd: gp.environment_sd,
             })
             .collect()
     }
 
-    pub fn update_ms_targets(
-        gp_dist: &Vec<GameParamsDistribution>,
-        realized_ms_targets: &HashMap<String, f32>,
+    pub fn with_share_sampling(
+        gp_dist: &[GameParamsDistribution],
+        share_sampling: ShareSampling,
     ) -> Vec<GameParamsDistribution> {
         gp_dist
             .iter()
             .cloned()
-            .map(|gp| GameParamsDistribution {
-                home: gp.home.update_ms_targets(realized_ms_targets),
-                away: gp.away.update_ms_targets(realized_ms_targets),
-                weather: gp.weather,
-                neutral_field: gp.neutral_field,
+            .map(|mut gp| {
+                gp.home.share_sampling = share_sampling;
+                gp.away.share_sampling = share_sampling;
+                gp
             })
             .collect()
     }
 }
 
+/// a rating for one sim, drawn around its point estimate
+pub fn sample_rating(point: f32, sd: f32) -> f32 {
+    match sd > 0.0 {
+        true => {
+            random_normal(point, sd).clamp(point - MAX_RATING_SDS * sd, point + MAX_RATING_SDS * sd)
+        }
+        false => point,
+    }
+}
+
 pub struct RushingParams {
     pub yoe_mean: f32,
     pub yoe_std: f32,
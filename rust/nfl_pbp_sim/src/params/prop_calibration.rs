@@ -0,0 +1,420 @@
+use csv::Reader;
+use serde::Deserialize;
+
+use crate::{
+    markets::player_stats::PlayerStat,
+    params::{
+        skill_player::SkillPlayerDistribution, GameParamsDistribution, TeamParamsDistribution,
+    },
+    projections::odds::american_odds_to_probability,
+    sim::{box_score::BoxScore, run::sim_box_scores_rayon},
+};
+
+// when a prop only lists the over, assume the book's usual two-way margin
+const DEFAULT_OVERROUND: f32 = 1.045;
+// how hard to push a parameter per unit of probability error
+const SHARE_GAIN: f32 = 1.5;
+const RYOE_GAIN: f32 = 2.0;
+const ADOT_GAIN: f32 = 8.0;
+
+const MAX_MARKET_SHARE: f32 = 0.6;
+const RYOE_BOUNDS: (f32, f32) = (-3.0, 3.0);
+const ADOT_BOUNDS: (f32, f32) = (-2.0, 20.0);
+
+#[derive(Debug, Deserialize)]
+pub struct PropLoader {
+    pub player_id: String,
+    pub stat: PlayerStat,
+    pub line: f32,
+    pub over_odds: f32,
+    pub under_odds: Option<f32>,
+}
+
+impl PropLoader {
+    pub fn load(path: &str) -> Vec<PropLoader> {
+        log::info!("loading player props from {}", path);
+        let mut reader =
+            Reader::from_path(path).unwrap_or_else(|_| panic!("Failed to load file at {}", path));
+        reader
+            .deserialize()
+            .map(|row| row.expect("failed to parse player prop"))
+            .collect()
+    }
+
+    /// de-vigged probability the player goes over
+    pub fn fair_over_prob(&self) -> f32 {
+        let over = american_odds_to_probability(self.over_odds);
+        match self.under_odds {
+            Some(under_odds) => over / (over + american_odds_to_probability(under_odds)),
+            None => over / DEFAULT_OVERROUND,
+        }
+    }
+}
+
+/// which input each stat's prop moves. targets and receptions props share one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PropParam {
+    MsTargets,
+    MsCarries,
+    Ryoe,
+    Adot,
+}
+
+impl PropParam {
+    pub fn for_stat(stat: PlayerStat) -> Option<PropParam> {
+        match stat {
+            PlayerStat::Targets | PlayerStat::Receptions => Some(PropParam::MsTargets),
+            PlayerStat::Carries => Some(PropParam::MsCarries),
+            PlayerStat::RushYards => Some(PropParam::Ryoe),
+            PlayerStat::RecYards => Some(PropParam::Adot),
+            _ => None,
+        }
+    }
+
+    fn value(&self, sp: &SkillPlayerDistribution) -> f32 {
+        match self {
+            PropParam::MsTargets => sp.ms_targets.mean(),
+            PropParam::MsCarries => sp.ms_carries.mean(),
+            PropParam::Ryoe => sp.ryoe,
+            PropParam::Adot => sp.adot,
+        }
+    }
+
+    /// move the parameter toward the market, returning false if it's stuck at a bound
+    fn nudge(&self, sp: &mut SkillPlayerDistribution, prob_short: f32) -> bool {
+        match self {
+            PropParam::MsTargets | PropParam::MsCarries => {
+                let share = match self {
+                    PropParam::MsTargets => &mut sp.ms_targets,
+                    _ => &mut sp.ms_carries,
+                };
+                if share.mean() == 0.0 {
+                    return false;
+                }
+                // a player already over the cap can still be scaled down
+                let old = share.mean();
+                let max_share = MAX_MARKET_SHARE.max(old);
+                *share = share.scaled((SHARE_GAIN * prob_short).exp(), max_share);
+                share.mean() != old
+            }
+            PropParam::Ryoe => {
+                let new = (sp.ryoe + RYOE_GAIN * prob_short).clamp(RYOE_BOUNDS.0, RYOE_BOUNDS.1);
+                let moved = new != sp.ryoe;
+                sp.ryoe = new;
+                moved
+            }
+            PropParam::Adot => {
+                let new = (sp.adot + ADOT_GAIN * prob_short).clamp(ADOT_BOUNDS.0, ADOT_BOUNDS.1);
+                let moved = new != sp.adot;
+                sp.adot = new;
+                moved
+            }
+        }
+    }
+
+    /// scale the player's teammates so the team's shares still sum to `team_total`
+    fn renormalize(&self, team: &mut TeamParamsDistribution, player_id: &str, team_total: f32) {
+        if !matches!(self, PropParam::MsTargets | PropParam::MsCarries) {
+            return;
+        }
+        let player_share = self.value(&team.skill_players[player_id]);
+        let others_total = team_total - player_share;
+        let others_share: f32 = team
+            .skill_players
+            .values()
+            .filter(|sp| sp.player_id != player_id)
+            .map(|sp| self.value(sp))
+            .sum();
+        if others_share <= 0.0 {
+            return;
+        }
+        let mult = others_total.max(0.0) / others_share;
+        for sp in team.skill_players.values_mut() {
+            if sp.player_id == player_id {
+                continue;
+            }
+            let share = match self {
+                PropParam::MsTargets => &mut sp.ms_targets,
+                _ => &mut sp.ms_carries,
+            };
+            *share = share.scaled(mult, MAX_MARKET_SHARE.max(share.mean()));
+        }
+    }
+}
+
+/// the combined miss of all a player's props on one parameter
+struct UsageNudge {
+    player_id: String,
+    param: PropParam,
+    prob_short: f32,
+    n_props: u32,
+    off_market: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PropCalibrationConfig {
+    pub n_sims: u32,
+    pub max_iterations: u32,
+    // how far P(over) may sit from the market
+    pub tolerance: f32,
+}
+
+impl Default for PropCalibrationConfig {
+    fn default() -> PropCalibrationConfig {
+        PropCalibrationConfig {
+            n_sims: 2_000,
+            max_iterations: 8,
+            tolerance: 0.03,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PropFit {
+    pub player_id: String,
+    pub name: String,
+    pub stat: PlayerStat,
+    pub line: f32,
+    pub market_prob: f32,
+    pub sim_prob: f32,
+    pub param: Option<PropParam>,
+    pub initial_value: f32,
+    pub fitted_value: f32,
+    pub fitted: bool,
+    // why the prop couldn't be fit
+    pub note: String,
+}
+
+fn find_team<'a>(
+    game_params_vec: &'a mut [GameParamsDistribution],
+    player_id: &str,
+) -> Option<(usize, &'a mut TeamParamsDistribution)> {
+    game_params_vec
+        .iter_mut()
+        .enumerate()
+        .find_map(|(game_ix, gp)| {
+            match (
+                gp.home.skill_players.contains_key(player_id),
+                gp.away.skill_players.contains_key(player_id),
+            ) {
+                (true, _) => Some((game_ix, &mut gp.home)),
+                (_, true) => Some((game_ix, &mut gp.away)),
+                _ => None,
+            }
+        })
+}
+
+fn find_player<'a>(
+    game_params_vec: &'a mut [GameParamsDistribution],
+    player_id: &str,
+) -> Option<(usize, &'a mut SkillPlayerDistribution)> {
+    find_team(game_params_vec, player_id)
+        .map(|(game_ix, team)| (game_ix, team.skill_players.get_mut(player_id).unwrap()))
+}
+
+fn sim_over_prob(sims: &[Vec<BoxScore>], game_ix: usize, prop: &PropFit) -> f32 {
+    let n = sims.len() as f32;
+    sims.iter()
+        .filter(|sim| {
+            prop.stat
+                .value(&sim[game_ix], &prop.player_id)
+                .unwrap_or(0.0)
+                > prop.line
+        })
+        .count() as f32
+        / n
+}
+
+/// adjust player usage and efficiency until P(over) on each prop matches the
+/// de-vigged market, stopping players whose parameter runs into its bounds.
+/// props on the same parameter move it together, by their mean miss, and usage
+/// changes are taken from or given back to the player's teammates
+pub fn calibrate_props(
+    game_params_vec: &[GameParamsDistribution],
+    props: &[PropLoader],
+    config: &PropCalibrationConfig,
+) -> (Vec<GameParamsDistribution>, Vec<PropFit>) {
+    let mut game_params_vec = game_params_vec.to_vec();
+    // (prop fit, game index), where props we can't fit at all have no game
+    let mut fits: Vec<(PropFit, Option<usize>)> = props
+        .iter()
+        .map(|prop| {
+            let param = PropParam::for_stat(prop.stat);
+            let player = find_player(&mut game_params_vec, &prop.player_id);
+            let note = match (&param, &player) {
+                (None, _) => String::from("unsupported stat"),
+                (_, None) => String::from("player not in params"),
+                _ => String::new(),
+            };
+            let (name, initial_value) = match (&param, &player) {
+                (Some(param), Some((_, sp))) => (sp.name.clone(), param.value(sp)),
+                (None, Some((_, sp))) => (sp.name.clone(), 0.0),
+                _ => (String::new(), 0.0),
+            };
+            let game_ix = match note.is_empty() {
+                true => player.map(|(game_ix, _)| game_ix),
+                false => None,
+            };
+            let fit = PropFit {
+                player_id: prop.player_id.clone(),
+                name,
+                stat: prop.stat,
+                line: prop.line,
+                market_prob: prop.fair_over_prob(),
+                sim_prob: 0.0,
+                param,
+                initial_value,
+                fitted_value: initial_value,
+                fitted: false,
+                note,
+            };
+            (fit, game_ix)
+        })
+        .collect();
+
+    for iteration in 1..=config.max_iterations {
+        let sims = sim_box_scores_rayon(
+            config.n_sims,
+            &game_params_vec,
+            false,
+            &format!("Prop calibration {}", iteration),
+        );
+        let mut n_unfitted = 0;
+        let mut nudges: Vec<UsageNudge> = vec![];
+        for (fit, game_ix) in fits.iter_mut() {
+            let game_ix = match game_ix {
+                Some(game_ix) => *game_ix,
+                None => continue,
+            };
+            fit.sim_prob = sim_over_prob(&sims, game_ix, fit);
+            let prob_short = fit.market_prob - fit.sim_prob;
+            fit.fitted = prob_short.abs() <= config.tolerance;
+            if !fit.note.is_empty() {
+                continue;
+            }
+            if !fit.fitted {
+                n_unfitted += 1;
+            }
+            // the last sims only check the fit
+            if iteration == config.max_iterations {
+                continue;
+            }
+            let param = fit.param.unwrap();
+            let ix = match nudges
+                .iter()
+                .position(|n| n.player_id == fit.player_id && n.param == param)
+            {
+                Some(ix) => ix,
+                None => {
+                    nudges.push(UsageNudge {
+                        player_id: fit.player_id.clone(),
+                        param,
+                        prob_short: 0.0,
+                        n_props: 0,
+                        off_market: false,
+                    });
+                    nudges.len() - 1
+                }
+            };
+            let nudge = &mut nudges[ix];
+            nudge.prob_short += prob_short;
+            nudge.n_props += 1;
+            nudge.off_market |= !fit.fitted;
+        }
+
+        for nudge in nudges.iter().filter(|n| n.off_market) {
+            let (_, team) = find_team(&mut game_params_vec, &nudge.player_id).unwrap();
+            let team_total: f32 = team
+                .skill_players
+                .values()
+                .map(|sp| nudge.param.value(sp))
+                .sum();
+            let sp = team.skill_players.get_mut(&nudge.player_id).unwrap();
+            if nudge
+                .param
+                .nudge(sp, nudge.prob_short / nudge.n_props as f32)
+            {
+                nudge.param.renormalize(team, &nudge.player_id, team_total);
+                continue;
+            }
+            for (fit, _) in fits.iter_mut() {
+                if fit.player_id == nudge.player_id && fit.param == Some(nudge.param) && !fit.fitted
+                {
+                    fit.note = String::from("parameter at its bound");
+                }
+            }
+        }
+        // teammates' shares move too
+        for (fit, game_ix) in fits.iter_mut() {
+            if let (Some(_), Some(param)) = (game_ix, fit.param) {
+                let (_, sp) = find_player(&mut game_params_vec, &fit.player_id).unwrap();
+                fit.fitted_value = param.value(sp);
+            }
+        }
+        log::info!(
+            "prop calibration iteration {}: {} props still off the market",
+            iteration,
+            n_unfitted
+        );
+        if n_unfitted == 0 {
+            break;
+        }
+    }
+
+    let fits: Vec<PropFit> = fits
+        .into_iter()
+        .map(|(mut fit, _)| {
+            if !fit.fitted && fit.note.is_empty() {
+                fit.note = String::from("did not converge");
+            }
+            fit
+        })
+        .collect();
+    for fit in fits.iter().filter(|fit| !fit.fitted) {
+        log::warn!(
+            "could not fit {} {} {}: {}",
+            fit.name,
+            fit.stat,
+            fit.line,
+            fit.note
+        );
+    }
+    (game_params_vec, fits)
+}
+
+pub fn write_prop_calibration_report(slate_dir: &str, fits: &[PropFit]) {
+    let proj_path = format!("{}/projections", slate_dir);
+    std::fs::create_dir_all(&proj_path).unwrap();
+    let mut wtr = csv::Writer::from_path(format!("{}/prop_calibration.csv", proj_path)).unwrap();
+    wtr.write_record([
+        "player_id",
+        "name",
+        "stat",
+        "line",
+        "market_prob",
+        "sim_prob",
+        "param",
+        "initial",
+        "fitted",
+        "ok",
+        "note",
+    ])
+    .unwrap();
+    for fit in fits {
+        wtr.write_record(&[
+            fit.player_id.clone(),
+            fit.name.clone(),
+            fit.stat.to_string(),
+            format!("{:.1}", fit.line),
+            format!("{:.3}", fit.market_prob),
+            format!("{:.3}", fit.sim_prob),
+            fit.param.map(|p| format!("{:?}", p)).unwrap_or_default(),
+            format!("{:.3}", fit.initial_value),
+            format!("{:.3}", fit.fitted_value),
+            fit.fitted.to_string(),
+            fit.note.clone(),
+        ])
+        .unwrap();
+    }
+    wtr.flush().unwrap();
+}
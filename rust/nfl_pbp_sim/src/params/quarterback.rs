@@ -1,11 +1,11 @@
 use std::collections::HashMap;
 
 // use crate::params::Injury;
-use crate::params::RushingParams;
+use crate::params::{sample_rating, RushingParams};
 use csv::Reader;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug)]
 pub struct Quarterback {
     pub player_id: String,
     pub team: String,
@@ -24,6 +24,10 @@ pub struct Quarterback {
     pub syoe_std: f32,
     // how likely are they to take sacks?
     pub prob_sack_given_hit: f32,
+    // standard deviations of the efficiencies above, redrawn for every sim
+    pub cpoe_sd: f32,
+    pub int_ue_sd: f32,
+    pub ayoe_sd: f32,
     // TODO: add depth chart/injuries
     // 1 for starter, 2 for first backup, etc.
     // pub depth_chart: u8,
@@ -43,12 +47,41 @@ impl Quarterback {
         }
     }
 
+    /// this sim's efficiencies, drawn around the point estimates
+    pub fn sample(&self) -> Quarterback {
+        let mut qb = self.clone();
+        qb.cpoe = sample_rating(self.cpoe, self.cpoe_sd);
+        qb.int_ue = sample_rating(self.int_ue, self.int_ue_sd);
+        qb.ayoe = sample_rating(self.ayoe, self.ayoe_sd);
+        qb
+    }
+
+    pub fn to_loader(&self) -> QuarterbackLoader {
+        QuarterbackLoader {
+            player_id: self.player_id.clone(),
+            team: self.team.clone(),
+            name: self.name.clone(),
+            cpoe: self.cpoe,
+            int_ue: self.int_ue,
+            ayoe: self.ayoe,
+            ay_std: self.ay_std,
+            scramble_rate: self.scramble_rate,
+            syoe: self.syoe,
+            syoe_std: self.syoe_std,
+            prob_sack_given_hit: self.prob_sack_given_hit,
+            cpoe_sd: Some(self.cpoe_sd),
+            int_ue_sd: Some(self.int_ue_sd),
+            ayoe_sd: Some(self.ayoe_sd),
+        }
+    }
+
     pub fn load(path: &String) -> HashMap<String, Vec<Quarterback>> {
         let mut qb_reader = Reader::from_path(format!("{}/QB-Table 1.csv", path)).unwrap();
 
         let mut qbs: HashMap<String, Vec<Quarterback>> = HashMap::new();
         for qb_csv in qb_reader.deserialize() {
-            let qb: Quarterback = qb_csv.expect("failed to parse qb");
+            let qb: QuarterbackLoader = qb_csv.expect("failed to parse qb");
+            let qb = qb.to_quarterback();
             match qbs.get_mut(&qb.team) {
                 Some(qb_vec) => qb_vec.push(qb),
                 None => {
@@ -59,3 +92,43 @@ impl Quarterback {
         qbs
     }
 }
+
+/// a row of the QB table, where the efficiency sds are optional
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct QuarterbackLoader {
+    pub player_id: String,
+    pub team: String,
+    pub name: String,
+    pub cpoe: f32,
+    pub int_ue: f32,
+    pub ayoe: f32,
+    pub ay_std: f32,
+    pub scramble_rate: f32,
+    pub syoe: f32,
+    pub syoe_std: f32,
+    pub prob_sack_given_hit: f32,
+    pub cpoe_sd: Option<f32>,
+    pub int_ue_sd: Option<f32>,
+    pub ayoe_sd: Option<f32>,
+}
+
+impl QuarterbackLoader {
+    pub fn to_quarterback(&self) -> Quarterback {
+        Quarterback {
+            player_id: self.player_id.clone(),
+            team: self.team.clone(),
+            name: self.name.clone(),
+            cpoe: self.cpoe,
+            int_ue: self.int_ue,
+            ayoe: self.ayoe,
+            ay_std: self.ay_std,
+            scramble_rate: self.scramble_rate,
+            syoe: self.syoe,
+            syoe_std: self.syoe_std,
+            prob_sack_given_hit: self.prob_sack_given_hit,
+            cpoe_sd: self.cpoe_sd.unwrap_or(0.0),
+            int_ue_sd: self.int_ue_sd.unwrap_or(0.0),
+            ayoe_sd: self.ayoe_sd.unwrap_or(0.0),
+        }
+    }
+}
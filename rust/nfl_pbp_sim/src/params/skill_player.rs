@@ -1,18 +1,30 @@
 use std::collections::HashMap;
 
-use crate::params::RushingParams;
-use crate::util::stats::sample_beta;
+use crate::params::{sample_rating, RushingParams};
+use crate::util::stats::{sample_beta, sample_dirichlet};
 
 use csv::Reader;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::params::Injury;
 
 pub const PROB_RZ_TARGET: f32 = 0.1307;
 pub const PROB_1YTG_GIVEN_CARRY: f32 = 0.102;
 pub const PROB_GZ_GIVEN_CARRY: f32 = 0.063;
+// floor on a team's dirichlet concentration when the stds are too wide to fit one
+const MIN_SHARE_CONCENTRATION: f32 = 1.0;
+
+/// how a team's market shares are drawn for each sim
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShareSampling {
+    // each player's beta on its own
+    #[default]
+    Independent,
+    // one dirichlet per team, so shares keep their total and teammates trade off usage
+    Joint,
+}
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum Position {
     #[serde(rename = "QB")]
     Quarterback,
@@ -103,6 +115,32 @@ impl MarketShare {
         (alpha, beta)
     }
 
+    pub fn mean(&self) -> f32 {
+        match self {
+            MarketShare::Constant(ms) => *ms,
+            MarketShare::Random(ms, _) => *ms,
+        }
+    }
+
+    pub fn std(&self) -> Option<f32> {
+        match self {
+            MarketShare::Constant(_) => None,
+            MarketShare::Random(_, std) => Some(*std),
+        }
+    }
+
+    /// scale the mean share, shrinking the std if it would no longer make a valid beta
+    pub fn scaled(&self, mult: f32, max_ms: f32) -> MarketShare {
+        match self {
+            MarketShare::Constant(ms) => MarketShare::Constant((ms * mult).min(max_ms)),
+            MarketShare::Random(ms, std) => {
+                let new_ms = (ms * mult).min(max_ms);
+                let max_std = 0.99 * (new_ms * (1.0 - new_ms)).sqrt();
+                MarketShare::Random(new_ms, std.min(max_std))
+            }
+        }
+    }
+
     pub fn collapse(&self) -> f32 {
         match self {
             MarketShare::Constant(ms) => *ms,
@@ -112,6 +150,45 @@ impl MarketShare {
             }
         }
     }
+
+    /// collapse a team's shares together. the random shares are drawn from a
+    /// dirichlet with the same means and one concentration fit to their stds,
+    /// and constant shares stay fixed
+    pub fn collapse_joint(shares: &[&MarketShare]) -> Vec<f32> {
+        let random: Vec<(usize, f32, f32)> = shares
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, share)| match share {
+                MarketShare::Random(ms, std) if *ms > 0.0 => Some((ix, *ms, *std)),
+                _ => None,
+            })
+            .collect();
+        // a lone random share would be pinned to its own mean
+        if random.len() < 2 {
+            return shares.iter().map(|share| share.collapse()).collect();
+        }
+
+        let total: f32 = random.iter().map(|(_, ms, _)| ms).sum();
+        // each player's std implies a concentration; weight them by share
+        let concentration = random
+            .iter()
+            .map(|(_, ms, std)| {
+                let (p, sd) = (ms / total, std / total);
+                p * (p * (1.0 - p) / sd.powi(2) - 1.0)
+            })
+            .sum::<f32>()
+            .max(MIN_SHARE_CONCENTRATION);
+        let alphas: Vec<f32> = random
+            .iter()
+            .map(|(_, ms, _)| concentration * ms / total)
+            .collect();
+
+        let mut collapsed: Vec<f32> = shares.iter().map(|share| share.mean()).collect();
+        for ((ix, _, _), draw) in random.iter().zip(sample_dirichlet(&alphas)) {
+            collapsed[*ix] = draw * total;
+        }
+        collapsed
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -180,6 +257,10 @@ pub struct SkillPlayerDistribution {
     pub prob_catch_oe: f32,
     pub xyac: f32,
     pub yac_oe: f32,
+    // uncertainty in the efficiencies, zero when treated as known
+    pub ryoe_sd: f32,
+    pub prob_catch_oe_sd: f32,
+    pub yac_oe_sd: f32,
     // misc ball carrying
     // TODO: fumble rate
     // pub fumble_rate: f32,
@@ -187,75 +268,72 @@ pub struct SkillPlayerDistribution {
 }
 
 impl SkillPlayerDistribution {
-    pub fn to_skill_player(&self) -> SkillPlayer {
-        let ms_carries_init = self.ms_carries.collapse();
-        let ms_targets_init = self.ms_targets.collapse();
-        SkillPlayer {
+    /// the table row this player loads from, so it can be written back out
+    pub fn to_loader(&self) -> SkillPlayerLoader {
+        SkillPlayerLoader {
             player_id: self.player_id.clone(),
             team: self.team.clone(),
             name: self.name.clone(),
-            position: self.position,
+            pos: self.position,
             depth_chart: self.depth_chart,
-            injury: Injury::Healthy,
-            ms_carries_init,
-            ms_carries_live: ms_carries_init,
-            ms_targets_init,
-            ms_targets_live: ms_targets_init,
-            prob_1ytg_given_carry: self.prob_1ytg_given_carry,
-            prob_gz_given_carry: self.prob_gz_given_carry,
-            ryoe: self.ryoe,
-            ryoe_std: self.ryoe_std,
-            prob_rz_given_target: self.prob_rz_given_target,
-            adot: self.adot,
-            adot_std: self.adot_std,
-            prob_catch_oe: self.prob_catch_oe,
-            xyac: self.xyac,
-            yac_oe: self.yac_oe,
-            injury_mult: self.injury_mult,
+            ms_carries: Some(self.ms_carries.mean()),
+            msc_std: self.ms_carries.std(),
+            ms_targets: Some(self.ms_targets.mean()),
+            mst_std: self.ms_targets.std(),
+            prob_1ytg_given_carry: Some(self.prob_1ytg_given_carry),
+            prob_gz_given_carry: Some(self.prob_gz_given_carry),
+            ryoe: Some(self.ryoe),
+            ryoe_std: Some(self.ryoe_std),
+            prob_rz_given_target: Some(self.prob_rz_given_target),
+            adot: Some(self.adot),
+            adot_std: Some(self.adot_std),
+            prob_catch_oe: Some(self.prob_catch_oe),
+            xyac: Some(self.xyac),
+            yac_oe: Some(self.yac_oe),
+            ryoe_sd: Some(self.ryoe_sd),
+            prob_catch_oe_sd: Some(self.prob_catch_oe_sd),
+            yac_oe_sd: Some(self.yac_oe_sd),
+            injury_mult: Some(self.injury_mult),
         }
     }
 
-    pub fn update_ms_targets(&self, realized_ms_targets: f32) -> SkillPlayerDistribution {
-        let ms_targets = match self.ms_targets {
-            MarketShare::Random(old_mean, std) => {
-                let new_mean = match old_mean == 0.0 {
-                    true => 0.0,
-                    false => old_mean.powi(2) / realized_ms_targets,
-                };
-                MarketShare::Random(new_mean, std)
-            }
-            MarketShare::Constant(old_mean) => {
-                let new_mean = match old_mean == 0.0 {
-                    true => 0.0,
-                    false => old_mean.powi(2) / realized_ms_targets,
-                };
-                MarketShare::Constant(new_mean)
-            }
-        };
-        SkillPlayerDistribution {
+    pub fn to_skill_player(&self) -> SkillPlayer {
+        self.to_skill_player_with_shares(self.ms_carries.collapse(), self.ms_targets.collapse())
+    }
+
+    /// the player for one sim, with market shares already drawn for the team
+    pub fn to_skill_player_with_shares(
+        &self,
+        ms_carries_init: f32,
+        ms_targets_init: f32,
+    ) -> SkillPlayer {
+        SkillPlayer {
             player_id: self.player_id.clone(),
             team: self.team.clone(),
             name: self.name.clone(),
             position: self.position,
             depth_chart: self.depth_chart,
-            ms_carries: self.ms_carries.clone(),
-            ms_targets,
+            injury: Injury::Healthy,
+            ms_carries_init,
+            ms_carries_live: ms_carries_init,
+            ms_targets_init,
+            ms_targets_live: ms_targets_init,
             prob_1ytg_given_carry: self.prob_1ytg_given_carry,
             prob_gz_given_carry: self.prob_gz_given_carry,
-            ryoe: self.ryoe,
+            ryoe: sample_rating(self.ryoe, self.ryoe_sd),
             ryoe_std: self.ryoe_std,
             prob_rz_given_target: self.prob_rz_given_target,
             adot: self.adot,
             adot_std: self.adot_std,
-            prob_catch_oe: self.prob_catch_oe,
+            prob_catch_oe: sample_rating(self.prob_catch_oe, self.prob_catch_oe_sd),
             xyac: self.xyac,
-            yac_oe: self.yac_oe,
+            yac_oe: sample_rating(self.yac_oe, self.yac_oe_sd),
             injury_mult: self.injury_mult,
         }
     }
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SkillPlayerLoader {
     pub player_id: String,
     pub team: String,
@@ -285,6 +363,11 @@ pub struct SkillPlayerLoader {
     pub prob_catch_oe: Option<f32>,
     pub xyac: Option<f32>,
     pub yac_oe: Option<f32>,
+    // how unsure we are of the efficiencies, not the per-play spread
+    // like `ryoe_std`
+    pub ryoe_sd: Option<f32>,
+    pub prob_catch_oe_sd: Option<f32>,
+    pub yac_oe_sd: Option<f32>,
     // misc ball carrying
     // TODO: fumble rate
     // pub fumble_rate: f32,
@@ -320,6 +403,9 @@ impl SkillPlayerLoader {
             prob_catch_oe: self.prob_catch_oe.unwrap_or(0.0),
             xyac: self.xyac.unwrap_or(5.5),
             yac_oe: self.yac_oe.unwrap_or(0.0),
+            ryoe_sd: self.ryoe_sd.unwrap_or(0.0),
+            prob_catch_oe_sd: self.prob_catch_oe_sd.unwrap_or(0.0),
+            yac_oe_sd: self.yac_oe_sd.unwrap_or(0.0),
             injury_mult: self.injury_mult.unwrap_or(1.0),
         }
     }
@@ -0,0 +1,775 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use csv::Reader;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::params::{GameParams, GameParamsDistribution};
+
+// for QBs without a scrambling baseline, e.g. a backup who's barely played
+const DEFAULT_SCRAMBLE_RATE: f32 = 0.05;
+const DEFAULT_SYOE: f32 = 0.0;
+const DEFAULT_SYOE_STD: f32 = 5.0;
+// passing for a QB without a passing baseline
+const REPLACEMENT_CPOE: f32 = -0.03;
+const REPLACEMENT_INT_UE: f32 = -0.01;
+const REPLACEMENT_AYOE: f32 = 0.0;
+const REPLACEMENT_AY_STD: f32 = 10.0;
+const REPLACEMENT_PROB_SACK_GIVEN_HIT: f32 = 0.5;
+// everyone past this on the depth chart shares the last slot
+const MAX_DEPTH: usize = 4;
+
+// overrides that pick rows instead of setting a column
+const ACTIVE_COLUMN: &str = "active";
+const KICKER_COLUMN: &str = "kicker_id";
+// also set like any other column, but read first so overridden players keep their slot
+const DEPTH_CHART_COLUMN: &str = "depth_chart";
+
+const TEAM_COLUMNS: [&str; 19] = [
+    "team",
+    "pace_z",
+    "offense_proe",
+    "offense_rz_proe",
+    "defense_proe",
+    "defense_rz_proe",
+    "oline_rushing_z",
+    "dline_rushing_z",
+    "offense_pass_rush_z",
+    "defense_pass_rush_z",
+    "defense_completion_z",
+    "defense_interception_z",
+    "defense_yac_oe",
+    "short_fg_z",
+    "long_fg_z",
+    "offense_penalty_z",
+    "defense_penalty_z",
+    "punt_returner_id",
+    "kickoff_returner_id",
+];
+const QB_COLUMNS: [&str; 11] = [
+    "player_id",
+    "team",
+    "name",
+    "cpoe",
+    "int_ue",
+    "ayoe",
+    "ay_std",
+    "scramble_rate",
+    "syoe",
+    "syoe_std",
+    "prob_sack_given_hit",
+];
+const SKILL_COLUMNS: [&str; 19] = [
+    "player_id",
+    "team",
+    "name",
+    "pos",
+    "depth_chart",
+    "ms_carries",
+    "msc_std",
+    "ms_targets",
+    "mst_std",
+    "prob_1ytg_given_carry",
+    "prob_gz_given_carry",
+    "ryoe",
+    "ryoe_std",
+    "prob_rz_given_target",
+    "prob_catch_oe",
+    "adot",
+    "adot_std",
+    "xyac",
+    "yac_oe",
+];
+
+#[derive(Debug, Deserialize)]
+struct TeamBaseline {
+    team: String,
+    pace_z: f32,
+    offense_proe: f32,
+    offense_rz_proe: f32,
+    defense_proe: f32,
+    defense_rz_proe: f32,
+    offense_penalty_z: f32,
+    defense_penalty_z: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct TeamRushingBaseline {
+    team: String,
+    oline_rushing_z: f32,
+    dline_rushing_z: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct TeamDropbacksBaseline {
+    team: String,
+    offense_pass_rush_z: f32,
+    defense_pass_rush_z: f32,
+    defense_completion_z: f32,
+    defense_interception_z: f32,
+    defense_yac_oe: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct CarriesBaseline {
+    team: String,
+    player_id: String,
+    name: String,
+    position: String,
+    ms_carries: f32,
+    prob_1ytg_given_carry: f32,
+    prob_gz_given_carry: f32,
+    ryoe: f32,
+    ryoe_std: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct PassCatcherBaseline {
+    player_id: String,
+    team: String,
+    name: String,
+    position: String,
+    ms_targets: f32,
+    prob_rz_given_target: f32,
+    prob_catch_oe: f32,
+    adot: f32,
+    adot_std: f32,
+    xyac: f32,
+    yac_oe: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QbBaseline {
+    player_id: String,
+    team: String,
+    name: String,
+    position: String,
+    cpoe: f32,
+    int_ue: f32,
+    ayoe: f32,
+    ay_std: f32,
+    prob_sack_given_hit: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScramblingBaseline {
+    player_id: String,
+    scramble_rate: f32,
+    syoe: Option<f32>,
+    syoe_std: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KickerBaseline {
+    player_id: String,
+    short_fg_z: f32,
+    long_fg_z: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReturnCount {
+    team: String,
+    player_id: String,
+    count: u32,
+}
+
+fn load_csv<T: DeserializeOwned>(path: &str) -> Vec<T> {
+    log::info!("loading {}", path);
+    let mut reader =
+        Reader::from_path(path).unwrap_or_else(|_| panic!("Failed to load file at {}", path));
+    reader
+        .deserialize()
+        .map(|row| row.unwrap_or_else(|e| panic!("failed to parse {}: {}", path, e)))
+        .collect()
+}
+
+/// the `data/baselines` tables the slate params are joined from
+pub struct Baselines {
+    teams: HashMap<String, TeamBaseline>,
+    team_rushing: HashMap<String, TeamRushingBaseline>,
+    team_dropbacks: HashMap<String, TeamDropbacksBaseline>,
+    carries: Vec<CarriesBaseline>,
+    pass_catchers: Vec<PassCatcherBaseline>,
+    qbs: Vec<QbBaseline>,
+    scrambling: HashMap<String, ScramblingBaseline>,
+    kickers: HashMap<String, KickerBaseline>,
+    punt_returns: Vec<ReturnCount>,
+    kickoff_returns: Vec<ReturnCount>,
+}
+
+impl Baselines {
+    pub fn load(baselines_dir: &str) -> Baselines {
+        let path = |file: &str| format!("{}/{}", baselines_dir, file);
+        Baselines {
+            teams: load_csv::<TeamBaseline>(&path("teams.csv"))
+                .into_iter()
+                .map(|t| (t.team.clone(), t))
+                .collect(),
+            team_rushing: load_csv::<TeamRushingBaseline>(&path("team_rushing.csv"))
+                .into_iter()
+                .map(|t| (t.team.clone(), t))
+                .collect(),
+            team_dropbacks: load_csv::<TeamDropbacksBaseline>(&path("team_dropbacks.csv"))
+                .into_iter()
+                .map(|t| (t.team.clone(), t))
+                .collect(),
+            carries: load_csv(&path("carries.csv")),
+            pass_catchers: load_csv(&path("pass_catchers.csv")),
+            qbs: load_csv(&path("qbs.csv")),
+            scrambling: load_csv::<ScramblingBaseline>(&path("scrambling.csv"))
+                .into_iter()
+                .map(|s| (s.player_id.clone(), s))
+                .collect(),
+            kickers: load_csv::<KickerBaseline>(&path("kickers.csv"))
+                .into_iter()
+                .map(|k| (k.player_id.clone(), k))
+                .collect(),
+            punt_returns: load_csv(&path("punt_return_counts.csv")),
+            kickoff_returns: load_csv(&path("kickoff_return_counts.csv")),
+        }
+    }
+}
+
+/// a manual fix to the built tables. `id` is a team or player id and `column`
+/// any column of its table, or `active` = 0 to leave a player off the slate
+/// and `kicker_id` to pick a team's kicker from the baselines
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SlateOverride {
+    pub id: String,
+    pub column: String,
+    pub value: String,
+}
+
+impl SlateOverride {
+    pub fn load(path: &str) -> Vec<SlateOverride> {
+        load_csv(path)
+    }
+}
+
+/// one of the `*-Table 1.csv` params tables, kept as text so an override can
+/// set any column. rows are keyed by their first column
+#[derive(Debug, Clone)]
+pub struct SlateTable {
+    pub name: &'static str,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl SlateTable {
+    fn new(name: &'static str, headers: &[&str]) -> SlateTable {
+        SlateTable {
+            name,
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: vec![],
+        }
+    }
+
+    fn column(&self, column: &str) -> Option<usize> {
+        self.headers.iter().position(|h| h == column)
+    }
+
+    /// set the column on every row keyed by `id`, returning whether any matched
+    fn set(&mut self, id: &str, column: &str, value: &str) -> bool {
+        let col = match self.column(column) {
+            Some(col) if col > 0 => col,
+            _ => return false,
+        };
+        let mut matched = false;
+        for row in self.rows.iter_mut().filter(|row| row[0] == id) {
+            row[col] = value.to_string();
+            matched = true;
+        }
+        matched
+    }
+
+    pub fn write(&self, params_dir: &str) {
+        std::fs::create_dir_all(params_dir).unwrap();
+        let path = format!("{}/{}-Table 1.csv", params_dir, self.name);
+        let mut wtr = csv::Writer::from_path(&path).unwrap();
+        wtr.write_record(&self.headers).unwrap();
+        for row in &self.rows {
+            wtr.write_record(row).unwrap();
+        }
+        wtr.flush().unwrap();
+        log::info!("wrote {} {} rows to {}", self.rows.len(), self.name, path);
+    }
+}
+
+fn fmt_opt(value: Option<f32>) -> String {
+    value.map_or(String::new(), |v| v.to_string())
+}
+
+/// a player on the slate, joined across the carries, pass catchers and QB tables
+struct Player<'a> {
+    player_id: &'a str,
+    name: &'a str,
+    position: &'a str,
+    carries: Option<&'a CarriesBaseline>,
+    pass_catcher: Option<&'a PassCatcherBaseline>,
+    passer: Option<&'a QbBaseline>,
+    ms_carries: f32,
+    ms_targets: f32,
+    depth_chart: usize,
+}
+
+impl Player<'_> {
+    /// what the depth chart is ranked on. carries say little about who starts
+    /// at QB, so the starter there has to come from an override
+    fn usage(&self) -> f32 {
+        match self.position {
+            "WR" | "TE" => self.ms_targets,
+            _ => self.ms_carries,
+        }
+    }
+
+    fn starters(&self) -> usize {
+        match self.position {
+            "WR" => 3,
+            _ => 1,
+        }
+    }
+
+    fn to_row(&self, team: &str) -> Vec<String> {
+        let carries = self.carries;
+        let catcher = self.pass_catcher;
+        vec![
+            self.player_id.to_string(),
+            team.to_string(),
+            self.name.to_string(),
+            self.position.to_string(),
+            self.depth_chart.to_string(),
+            carries.map_or(String::new(), |_| format!("{:.4}", self.ms_carries)),
+            String::new(),
+            catcher.map_or(String::new(), |_| format!("{:.4}", self.ms_targets)),
+            String::new(),
+            fmt_opt(carries.map(|c| c.prob_1ytg_given_carry)),
+            fmt_opt(carries.map(|c| c.prob_gz_given_carry)),
+            fmt_opt(carries.map(|c| c.ryoe)),
+            fmt_opt(carries.map(|c| c.ryoe_std)),
+            fmt_opt(catcher.map(|c| c.prob_rz_given_target)),
+            fmt_opt(catcher.map(|c| c.prob_catch_oe)),
+            fmt_opt(catcher.map(|c| c.adot)),
+            fmt_opt(catcher.map(|c| c.adot_std)),
+            fmt_opt(catcher.map(|c| c.xyac)),
+            fmt_opt(catcher.and_then(|c| c.yac_oe)),
+        ]
+    }
+}
+
+/// the four params tables for a slate
+#[derive(Debug, Clone)]
+pub struct SlateTables {
+    pub games: SlateTable,
+    pub teams: SlateTable,
+    pub qbs: SlateTable,
+    pub skill_players: SlateTable,
+}
+
+impl SlateTables {
+    /// join the baselines for every team in the schedule, which is a games
+    /// table (home, away, time, stadium_type, ...), then apply the overrides
+    pub fn build(
+        baselines: &Baselines,
+        schedule_path: &str,
+        overrides: &[SlateOverride],
+    ) -> SlateTables {
+        let games = load_schedule(schedule_path);
+        let (home_col, away_col) = (
+            games.column("home").expect("schedule has no home column"),
+            games.column("away").expect("schedule has no away column"),
+        );
+        let teams: Vec<String> = games
+            .rows
+            .iter()
+            .flat_map(|row| [row[home_col].clone(), row[away_col].clone()])
+            .collect();
+
+        let inactive: HashSet<&str> = overrides
+            .iter()
+            .filter(|o| o.column == ACTIVE_COLUMN && o.value.trim() == "0")
+            .map(|o| o.id.as_str())
+            .collect();
+        let kickers: HashMap<&str, &str> = overrides
+            .iter()
+            .filter(|o| o.column == KICKER_COLUMN)
+            .map(|o| (o.id.as_str(), o.value.as_str()))
+            .collect();
+        let depth_charts: HashMap<&str, usize> = overrides
+            .iter()
+            .filter(|o| o.column == DEPTH_CHART_COLUMN)
+            .map(|o| {
+                let depth = o
+                    .value
+                    .trim()
+                    .parse()
+                    .unwrap_or_else(|_| panic!("bad depth chart override for {}", o.id));
+                (o.id.as_str(), depth)
+            })
+            .collect();
+
+        let mut tables = SlateTables {
+            games,
+            teams: SlateTable::new("Teams", &TEAM_COLUMNS),
+            qbs: SlateTable::new("QB", &QB_COLUMNS),
+            skill_players: SlateTable::new("Skill", &SKILL_COLUMNS),
+        };
+        for team in &teams {
+            let kicker_id = kickers.get(team.as_str()).copied();
+            tables
+                .teams
+                .rows
+                .push(team_row(baselines, team, kicker_id, &inactive));
+            tables.qbs.rows.extend(qb_rows(baselines, team, &inactive));
+            tables
+                .skill_players
+                .rows
+                .extend(skill_rows(baselines, team, &inactive, &depth_charts));
+        }
+
+        for o in overrides {
+            if o.column == ACTIVE_COLUMN || o.column == KICKER_COLUMN {
+                continue;
+            }
+            let mut matched = false;
+            for table in [
+                &mut tables.games,
+                &mut tables.teams,
+                &mut tables.qbs,
+                &mut tables.skill_players,
+            ] {
+                matched |= table.set(&o.id, &o.column, &o.value);
+            }
+            if !matched {
+                log::warn!("override of {} for {} matched no row", o.column, o.id);
+            }
+        }
+
+        tables.order_qbs(&teams);
+        tables
+    }
+
+    /// QBs are listed starter first, after any depth chart overrides
+    fn order_qbs(&mut self, teams: &[String]) {
+        let depth_col = self.skill_players.column("depth_chart").unwrap();
+        let depths: HashMap<String, u8> = self
+            .skill_players
+            .rows
+            .iter()
+            .map(|row| {
+                let depth = row[depth_col]
+                    .parse()
+                    .unwrap_or_else(|_| panic!("bad depth chart for {}", row[0]));
+                (row[0].clone(), depth)
+            })
+            .collect();
+        let team_ix = |team: &str| teams.iter().position(|t| t == team);
+        self.qbs
+            .rows
+            .sort_by_key(|row| (team_ix(&row[1]), depths.get(&row[0]).copied()));
+
+        for team in teams {
+            let qb_depths: Vec<u8> = self
+                .qbs
+                .rows
+                .iter()
+                .filter(|row| &row[1] == team)
+                .filter_map(|row| depths.get(&row[0]).copied())
+                .collect();
+            assert!(
+                qb_depths.contains(&1) && qb_depths.contains(&2),
+                "{} needs QBs at depth 1 and 2, has {:?}",
+                team,
+                qb_depths
+            );
+        }
+    }
+
+    pub fn write(&self, params_dir: &str) {
+        for table in [&self.games, &self.teams, &self.qbs, &self.skill_players] {
+            table.write(params_dir);
+        }
+    }
+}
+
+fn load_schedule(path: &str) -> SlateTable {
+    log::info!("loading schedule from {}", path);
+    let mut reader =
+        Reader::from_path(path).unwrap_or_else(|_| panic!("Failed to load file at {}", path));
+    let headers: Vec<String> = reader
+        .headers()
+        .expect("failed to read schedule headers")
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+    SlateTable {
+        name: "Games",
+        headers,
+        rows: reader
+            .records()
+            .map(|row| {
+                row.expect("failed to parse schedule")
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect()
+            })
+            .collect(),
+    }
+}
+
+/// the active player with the most returns for the team
+fn top_returner(counts: &[ReturnCount], team: &str, inactive: &HashSet<&str>) -> String {
+    counts
+        .iter()
+        .filter(|r| r.team == team && !inactive.contains(r.player_id.as_str()))
+        .max_by_key(|r| r.count)
+        .map_or(String::new(), |r| r.player_id.clone())
+}
+
+fn team_row(
+    baselines: &Baselines,
+    team: &str,
+    kicker_id: Option<&str>,
+    inactive: &HashSet<&str>,
+) -> Vec<String> {
+    let tm = baselines
+        .teams
+        .get(team)
+        .unwrap_or_else(|| panic!("no team baseline for {}", team));
+    let rushing = &baselines.team_rushing[team];
+    let dropbacks = &baselines.team_dropbacks[team];
+    let (short_fg_z, long_fg_z) = match kicker_id.map(|id| baselines.kickers.get(id)) {
+        Some(Some(kicker)) => (kicker.short_fg_z, kicker.long_fg_z),
+        Some(None) => panic!("no kicker baseline for {}", kicker_id.unwrap()),
+        None => {
+            log::warn!(
+                "no kicker_id override for {}, using an average kicker",
+                team
+            );
+            (0.0, 0.0)
+        }
+    };
+    vec![
+        team.to_string(),
+        tm.pace_z.to_string(),
+        tm.offense_proe.to_string(),
+        tm.offense_rz_proe.to_string(),
+        tm.defense_proe.to_string(),
+        tm.defense_rz_proe.to_string(),
+        rushing.oline_rushing_z.to_string(),
+        rushing.dline_rushing_z.to_string(),
+        dropbacks.offense_pass_rush_z.to_string(),
+        dropbacks.defense_pass_rush_z.to_string(),
+        dropbacks.defense_completion_z.to_string(),
+        dropbacks.defense_interception_z.to_string(),
+        dropbacks.defense_yac_oe.to_string(),
+        short_fg_z.to_string(),
+        long_fg_z.to_string(),
+        tm.offense_penalty_z.to_string(),
+        tm.defense_penalty_z.to_string(),
+        top_returner(&baselines.punt_returns, team, inactive),
+        top_returner(&baselines.kickoff_returns, team, inactive),
+    ]
+}
+
+/// every active QB with a passing or carry baseline. a QB who has only run
+/// the ball gets replacement level passing
+fn qb_rows(baselines: &Baselines, team: &str, inactive: &HashSet<&str>) -> Vec<Vec<String>> {
+    let mut passers: BTreeMap<&str, (&str, Option<&QbBaseline>)> = BTreeMap::new();
+    for qb in baselines.qbs.iter().filter(|qb| qb.team == team) {
+        if qb.position == "QB" {
+            passers.insert(&qb.player_id, (&qb.name, Some(qb)));
+        }
+    }
+    for c in baselines.carries.iter().filter(|c| c.team == team) {
+        if c.position == "QB" {
+            passers.entry(&c.player_id).or_insert((&c.name, None));
+        }
+    }
+
+    passers
+        .into_iter()
+        .filter(|(player_id, _)| !inactive.contains(player_id))
+        .map(|(player_id, (name, qb))| {
+            let scrambling = baselines.scrambling.get(player_id);
+            vec![
+                player_id.to_string(),
+                team.to_string(),
+                name.to_string(),
+                qb.map_or(REPLACEMENT_CPOE, |qb| qb.cpoe).to_string(),
+                qb.map_or(REPLACEMENT_INT_UE, |qb| qb.int_ue).to_string(),
+                qb.map_or(REPLACEMENT_AYOE, |qb| qb.ayoe).to_string(),
+                qb.map_or(REPLACEMENT_AY_STD, |qb| qb.ay_std).to_string(),
+                scrambling
+                    .map_or(DEFAULT_SCRAMBLE_RATE, |s| s.scramble_rate)
+                    .to_string(),
+                scrambling
+                    .and_then(|s| s.syoe)
+                    .unwrap_or(DEFAULT_SYOE)
+                    .to_string(),
+                scrambling
+                    .and_then(|s| s.syoe_std)
+                    .unwrap_or(DEFAULT_SYOE_STD)
+                    .to_string(),
+                qb.map_or(REPLACEMENT_PROB_SACK_GIVEN_HIT, |qb| qb.prob_sack_given_hit)
+                    .to_string(),
+            ]
+        })
+        .collect()
+}
+
+/// the player's entry on the roster, or none if they're inactive
+fn roster_entry<'a, 'r>(
+    players: &'r mut BTreeMap<&'a str, Player<'a>>,
+    inactive: &HashSet<&str>,
+    player_id: &'a str,
+    name: &'a str,
+    position: &'a str,
+) -> Option<&'r mut Player<'a>> {
+    if inactive.contains(player_id) {
+        return None;
+    }
+    Some(players.entry(player_id).or_insert(Player {
+        player_id,
+        name,
+        position,
+        carries: None,
+        pass_catcher: None,
+        passer: None,
+        ms_carries: 0.0,
+        ms_targets: 0.0,
+        depth_chart: MAX_DEPTH,
+    }))
+}
+
+/// every active player with a carry, target or QB baseline. shares are
+/// renormalised over the active players and depth charts follow share rank,
+/// after the players with a depth chart override
+fn skill_rows(
+    baselines: &Baselines,
+    team: &str,
+    inactive: &HashSet<&str>,
+    depth_charts: &HashMap<&str, usize>,
+) -> Vec<Vec<String>> {
+    let mut players: BTreeMap<&str, Player> = BTreeMap::new();
+    for c in baselines.carries.iter().filter(|c| c.team == team) {
+        if let Some(p) = roster_entry(&mut players, inactive, &c.player_id, &c.name, &c.position) {
+            p.carries = Some(c);
+            p.ms_carries = c.ms_carries;
+        }
+    }
+    for c in baselines.pass_catchers.iter().filter(|c| c.team == team) {
+        if let Some(p) = roster_entry(&mut players, inactive, &c.player_id, &c.name, &c.position) {
+            p.pass_catcher = Some(c);
+            p.ms_targets = c.ms_targets;
+        }
+    }
+    for qb in baselines
+        .qbs
+        .iter()
+        .filter(|qb| qb.team == team && qb.position == "QB")
+    {
+        if let Some(p) = roster_entry(
+            &mut players,
+            inactive,
+            &qb.player_id,
+            &qb.name,
+            &qb.position,
+        ) {
+            p.passer = Some(qb);
+        }
+    }
+
+    let total_carries: f32 = players.values().map(|p| p.ms_carries).sum();
+    let total_targets: f32 = players.values().map(|p| p.ms_targets).sum();
+    for p in players.values_mut() {
+        if total_carries > 0.0 {
+            p.ms_carries /= total_carries;
+        }
+        if total_targets > 0.0 {
+            p.ms_targets /= total_targets;
+        }
+    }
+
+    let mut by_position: HashMap<&str, Vec<&mut Player>> = HashMap::new();
+    for p in players.values_mut() {
+        by_position.entry(p.position).or_default().push(p);
+    }
+    let override_depth = |p: &Player| depth_charts.get(p.player_id).copied();
+    for (position, group) in by_position.iter_mut() {
+        // QBs with a passing baseline go ahead of the carry-only ones
+        let is_qb = *position == "QB";
+        group.sort_by(|a, b| {
+            (is_qb && a.passer.is_none())
+                .cmp(&(is_qb && b.passer.is_none()))
+                .then(b.usage().total_cmp(&a.usage()))
+        });
+
+        // the slots the group's ranks would get, less the ones overridden players hold
+        let starters = group[0].starters();
+        let mut slots: Vec<usize> = (0..group.len())
+            .map(|rank| match rank < starters {
+                true => 1,
+                false => (rank - starters + 2).min(MAX_DEPTH),
+            })
+            .collect();
+        for p in group.iter_mut() {
+            if let Some(depth) = override_depth(p) {
+                p.depth_chart = depth;
+                if let Some(ix) = slots.iter().position(|slot| *slot == depth) {
+                    slots.remove(ix);
+                }
+            }
+        }
+
+        let mut ranked: Vec<&mut &mut Player> = group
+            .iter_mut()
+            .filter(|p| override_depth(p).is_none())
+            .collect();
+        if is_qb && !ranked.is_empty() && slots.first() == Some(&1) {
+            log::warn!(
+                "no depth_chart override for {}'s starting QB, starting {} on carry share. add `{},depth_chart,1` to overrides.csv if that's wrong",
+                team,
+                ranked[0].name,
+                ranked[0].player_id
+            );
+        }
+        if is_qb && ranked.len() > 1 && ranked[0].usage() == ranked[1].usage() {
+            log::warn!(
+                "{} QBs {} and {} tie on carry share, add a depth_chart override to order them",
+                team,
+                ranked[0].name,
+                ranked[1].name
+            );
+        }
+        for (p, slot) in ranked.iter_mut().zip(slots) {
+            p.depth_chart = slot;
+        }
+    }
+
+    // listed like a depth chart, so the table is easy to check by hand
+    let position_order = |position: &str| {
+        ["QB", "RB", "FB", "TE", "WR"]
+            .iter()
+            .position(|p| *p == position)
+    };
+    let mut players: Vec<&Player> = players.values().collect();
+    players.sort_by(|a, b| {
+        (position_order(a.position), a.depth_chart)
+            .cmp(&(position_order(b.position), b.depth_chart))
+            .then(b.usage().total_cmp(&a.usage()))
+    });
+    players.iter().map(|p| p.to_row(team)).collect()
+}
+
+/// build the slate's params tables from the baselines, write them to
+/// `params_dir` and load them back
+pub fn build_slate_params(
+    baselines_dir: &str,
+    schedule_path: &str,
+    overrides_path: Option<&str>,
+    params_dir: &str,
+) -> Vec<GameParamsDistribution> {
+    let baselines = Baselines::load(baselines_dir);
+    let overrides = overrides_path.map_or(vec![], SlateOverride::load);
+    SlateTables::build(&baselines, schedule_path, &overrides).write(params_dir);
+    GameParams::load(params_dir.to_string())
+}
@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::params::{
+    quarterback::QuarterbackLoader,
+    skill_player::{ShareSampling, SkillPlayerLoader},
+    slate_builder::SlateOverride,
+    team::TeamLoader,
+    weather::{StadiumType, Weather},
+    GameParamsDistribution, MarketLine, TeamParamsDistribution,
+};
+
+pub const SLATE_SCHEMA_VERSION: u32 = 1;
+
+/// upgrades for older slate files, oldest first: `MIGRATIONS[i]` takes a
+/// version `i + 1` slate to version `i + 2`. a schema bump needs one here
+const MIGRATIONS: [fn(&mut Value); (SLATE_SCHEMA_VERSION - 1) as usize] = [];
+
+/// a whole slate in one file, as an alternative to the four params tables
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlateFile {
+    pub schema_version: u32,
+    pub games: Vec<SlateGame>,
+    // same as overrides.csv for the slate builder, applied when the params are built
+    #[serde(default)]
+    pub overrides: Vec<SlateOverride>,
+}
+
+/// a row of the games table, with both teams nested in it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlateGame {
+    pub home: SlateTeam,
+    pub away: SlateTeam,
+    pub time: String,
+    pub stadium_type: StadiumType,
+    #[serde(default)]
+    pub neutral_field: bool,
+    #[serde(default)]
+    pub spread: Option<f32>,
+    #[serde(default)]
+    pub total: Option<f32>,
+    #[serde(default)]
+    pub environment_sd: f32,
+}
+
+/// a row of the teams table, with its QBs and skill players
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlateTeam {
+    #[serde(flatten)]
+    pub team: TeamLoader,
+    // burned-in situational rates, league average when missing
+    #[serde(default)]
+    pub prob_1ytg_given_carry: Option<f32>,
+    #[serde(default)]
+    pub prob_gz_given_carry: Option<f32>,
+    #[serde(default)]
+    pub prob_rz_given_target: Option<f32>,
+    pub qbs: Vec<QuarterbackLoader>,
+    pub skill_players: Vec<SkillPlayerLoader>,
+}
+
+impl SlateTeam {
+    pub fn from_distribution(tp: &TeamParamsDistribution) -> SlateTeam {
+        let mut skill_players: Vec<SkillPlayerLoader> =
+            tp.skill_players.values().map(|sp| sp.to_loader()).collect();
+        skill_players.sort_by(|a, b| {
+            (a.pos, a.depth_chart, &a.player_id).cmp(&(b.pos, b.depth_chart, &b.player_id))
+        });
+        SlateTeam {
+            team: tp.team.to_loader(),
+            prob_1ytg_given_carry: Some(tp.team.prob_1ytg_given_carry),
+            prob_gz_given_carry: Some(tp.team.prob_gz_given_carry),
+            prob_rz_given_target: Some(tp.team.prob_rz_given_target),
+            qbs: tp.qbs.iter().map(|qb| qb.to_loader()).collect(),
+            skill_players,
+        }
+    }
+
+    pub fn to_distribution(&self) -> TeamParamsDistribution {
+        let mut team = self.team.to_team();
+        if let Some(prob) = self.prob_1ytg_given_carry {
+            team.prob_1ytg_given_carry = prob;
+        }
+        if let Some(prob) = self.prob_gz_given_carry {
+            team.prob_gz_given_carry = prob;
+        }
+        if let Some(prob) = self.prob_rz_given_target {
+            team.prob_rz_given_target = prob;
+        }
+        TeamParamsDistribution {
+            team,
+            qbs: self.qbs.iter().map(|qb| qb.to_quarterback()).collect(),
+            skill_players: self
+                .skill_players
+                .iter()
+                .map(|sp| (sp.player_id.clone(), sp.fill_na()))
+                .collect(),
+            share_sampling: ShareSampling::Independent,
+        }
+    }
+}
+
+impl SlateGame {
+    pub fn from_distribution(gp: &GameParamsDistribution) -> SlateGame {
+        SlateGame {
+            home: SlateTeam::from_distribution(&gp.home),
+            away: SlateTeam::from_distribution(&gp.away),
+            time: gp.weather.start_time.clone(),
+            stadium_type: gp.weather.stadium_type.clone(),
+            neutral_field: gp.neutral_field,
+            spread: gp.market.map(|m| m.spread),
+            total: gp.market.map(|m| m.total),
+            environment_sd: gp.environment_sd,
+        }
+    }
+
+    pub fn to_distribution(&self) -> GameParamsDistribution {
+        GameParamsDistribution {
+            home: self.home.to_distribution(),
+            away: self.away.to_distribution(),
+            weather: Weather {
+                stadium_type: self.stadium_type.clone(),
+                start_time: self.time.clone(),
+            },
+            neutral_field: self.neutral_field,
+            market: match (self.spread, self.total) {
+                (Some(spread), Some(total)) => Some(MarketLine { spread, total }),
+                _ => None,
+            },
+            environment_sd: self.environment_sd,
+        }
+    }
+}
+
+impl SlateFile {
+    pub fn from_game_params(game_params: &[GameParamsDistribution]) -> SlateFile {
+        SlateFile {
+            schema_version: SLATE_SCHEMA_VERSION,
+            games: game_params
+                .iter()
+                .map(SlateGame::from_distribution)
+                .collect(),
+            overrides: vec![],
+        }
+    }
+
+    /// the slate's params, after the overrides
+    pub fn to_game_params(&self) -> Vec<GameParamsDistribution> {
+        let mut games = serde_json::to_value(&self.games).unwrap();
+        for o in &self.overrides {
+            if !apply_override(&mut games, o) {
+                log::warn!("override of {} for {} matched nothing", o.column, o.id);
+            }
+        }
+        let games: Vec<SlateGame> =
+            serde_json::from_value(games).expect("overrides left an invalid slate");
+        games.iter().map(SlateGame::to_distribution).collect()
+    }
+
+    /// read a slate file, upgrading it from an older schema version if need be
+    pub fn load(path: &str) -> SlateFile {
+        log::info!("loading slate from {}", path);
+        let text = std::fs::read_to_string(path)
+            .unwrap_or_else(|_| panic!("Failed to load file at {}", path));
+        let mut value: Value = serde_json::from_str(&text)
+            .unwrap_or_else(|e| panic!("failed to parse slate {}: {}", path, e));
+        migrate(&mut value);
+        serde_json::from_value(value)
+            .unwrap_or_else(|e| panic!("failed to parse slate {}: {}", path, e))
+    }
+
+    pub fn write(&self, path: &str) {
+        if let Some(dir) = std::path::Path::new(path).parent() {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        let file = std::fs::File::create(path).unwrap();
+        serde_json::to_writer_pretty(file, self).unwrap();
+        log::info!("wrote slate to {}", path);
+    }
+}
+
+fn migrate(value: &mut Value) {
+    let version = value["schema_version"]
+        .as_u64()
+        .expect("slate has no schema_version") as u32;
+    assert!(
+        (1..=SLATE_SCHEMA_VERSION).contains(&version),
+        "slate schema version {} isn't supported, the latest is {}",
+        version,
+        SLATE_SCHEMA_VERSION
+    );
+    for (ix, migration) in MIGRATIONS.iter().enumerate().skip(version as usize - 1) {
+        log::info!("migrating slate from schema version {}", ix + 1);
+        migration(value);
+    }
+    value["schema_version"] = Value::from(SLATE_SCHEMA_VERSION);
+}
+
+/// set the column on every game, team or player keyed by the override's id that
+/// has it, or take the player off the slate for `active` = 0. games are keyed by
+/// their home team, like the games table
+fn apply_override(games: &mut Value, o: &SlateOverride) -> bool {
+    let value = serde_json::from_str(&o.value).unwrap_or(Value::String(o.value.clone()));
+    let set = |object: &mut Value, key: &str| match object.get(key) {
+        Some(id) if id == o.id.as_str() && object.get(&o.column).is_some() => {
+            object[&o.column] = value.clone();
+            true
+        }
+        _ => false,
+    };
+
+    let mut matched = false;
+    for game in games.as_array_mut().unwrap() {
+        let home = game["home"]["team"].clone();
+        if home == o.id.as_str() && game.get(&o.column).is_some() {
+            game[&o.column] = value.clone();
+            matched = true;
+        }
+        for side in ["home", "away"] {
+            let team = &mut game[side];
+            matched |= set(team, "team");
+            for players in ["qbs", "skill_players"] {
+                let players = team[players].as_array_mut().unwrap();
+                if o.column == "active" && o.value.trim() == "0" {
+                    let n_players = players.len();
+                    players.retain(|p| p["player_id"] != o.id.as_str());
+                    matched |= players.len() < n_players;
+                    continue;
+                }
+                for player in players.iter_mut() {
+                    matched |= set(player, "player_id");
+                }
+            }
+        }
+    }
+    matched
+}
@@ -1,11 +1,19 @@
 use std::collections::HashMap;
 
 use csv::Reader;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::params::skill_player::{PROB_1YTG_GIVEN_CARRY, PROB_GZ_GIVEN_CARRY, PROB_RZ_TARGET};
+use crate::params::{
+    sample_rating,
+    skill_player::{PROB_1YTG_GIVEN_CARRY, PROB_GZ_GIVEN_CARRY, PROB_RZ_TARGET},
+};
 
-#[derive(Debug, Clone, Deserialize)]
+// how far one unit of game environment moves each offense
+const PACE_Z_PER_ENVIRONMENT: f32 = 1.0;
+const PROE_PER_ENVIRONMENT: f32 = 0.03;
+const EFFICIENCY_PER_ENVIRONMENT: f32 = 1.0;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TeamLoader {
     // general
     pub team: String,
@@ -37,6 +45,28 @@ pub struct TeamLoader {
 
     pub kickoff_returner_id: Option<String>,
     pub punt_returner_id: Option<String>,
+
+    // optional, e.g. kept from an earlier market calibration
+    pub offense_efficiency: Option<f32>,
+
+    // optional standard deviations of the ratings above, for how unsure we are
+    // of them. a fresh rating is drawn for every sim
+    pub pace_z_sd: Option<f32>,
+    pub offense_proe_sd: Option<f32>,
+    pub offense_rz_proe_sd: Option<f32>,
+    pub oline_rushing_z_sd: Option<f32>,
+    pub offense_pass_rush_z_sd: Option<f32>,
+    pub defense_proe_sd: Option<f32>,
+    pub defense_rz_proe_sd: Option<f32>,
+    pub dline_rushing_z_sd: Option<f32>,
+    pub defense_pass_rush_z_sd: Option<f32>,
+    pub defense_completion_z_sd: Option<f32>,
+    pub defense_interception_z_sd: Option<f32>,
+    pub defense_yac_oe_sd: Option<f32>,
+    pub short_fg_z_sd: Option<f32>,
+    pub long_fg_z_sd: Option<f32>,
+    pub offense_penalty_z_sd: Option<f32>,
+    pub defense_penalty_z_sd: Option<f32>,
 }
 
 impl TeamLoader {
@@ -61,6 +91,25 @@ impl TeamLoader {
             defense_penalty_z: self.defense_penalty_z,
             kickoff_returner_id: self.kickoff_returner_id.clone(),
             punt_returner_id: self.punt_returner_id.clone(),
+            offense_efficiency: self.offense_efficiency.unwrap_or(0.0),
+            ratings_sd: TeamRatingsSd {
+                pace_z: self.pace_z_sd.unwrap_or(0.0),
+                offense_proe: self.offense_proe_sd.unwrap_or(0.0),
+                offense_rz_proe: self.offense_rz_proe_sd.unwrap_or(0.0),
+                oline_rushing_z: self.oline_rushing_z_sd.unwrap_or(0.0),
+                offense_pass_rush_z: self.offense_pass_rush_z_sd.unwrap_or(0.0),
+                defense_proe: self.defense_proe_sd.unwrap_or(0.0),
+                defense_rz_proe: self.defense_rz_proe_sd.unwrap_or(0.0),
+                dline_rushing_z: self.dline_rushing_z_sd.unwrap_or(0.0),
+                defense_pass_rush_z: self.defense_pass_rush_z_sd.unwrap_or(0.0),
+                defense_completion_z: self.defense_completion_z_sd.unwrap_or(0.0),
+                defense_interception_z: self.defense_interception_z_sd.unwrap_or(0.0),
+                defense_yac_oe: self.defense_yac_oe_sd.unwrap_or(0.0),
+                short_fg_z: self.short_fg_z_sd.unwrap_or(0.0),
+                long_fg_z: self.long_fg_z_sd.unwrap_or(0.0),
+                offense_penalty_z: self.offense_penalty_z_sd.unwrap_or(0.0),
+                defense_penalty_z: self.defense_penalty_z_sd.unwrap_or(0.0),
+            },
             prob_1ytg_given_carry: PROB_1YTG_GIVEN_CARRY,
             prob_gz_given_carry: PROB_GZ_GIVEN_CARRY,
             prob_rz_given_target: PROB_RZ_TARGET,
@@ -68,6 +117,27 @@ impl TeamLoader {
     }
 }
 
+/// standard deviation of each team rating
+#[derive(Debug, Clone, Default)]
+pub struct TeamRatingsSd {
+    pub pace_z: f32,
+    pub offense_proe: f32,
+    pub offense_rz_proe: f32,
+    pub oline_rushing_z: f32,
+    pub offense_pass_rush_z: f32,
+    pub defense_proe: f32,
+    pub defense_rz_proe: f32,
+    pub dline_rushing_z: f32,
+    pub defense_pass_rush_z: f32,
+    pub defense_completion_z: f32,
+    pub defense_interception_z: f32,
+    pub defense_yac_oe: f32,
+    pub short_fg_z: f32,
+    pub long_fg_z: f32,
+    pub offense_penalty_z: f32,
+    pub defense_penalty_z: f32,
+}
+
 #[derive(Debug, Clone)]
 pub struct Team {
     // general
@@ -101,6 +171,12 @@ pub struct Team {
     pub kickoff_returner_id: Option<String>,
     pub punt_returner_id: Option<String>,
 
+    // how much better the offense moves the ball than its inputs say,
+    // tuned so sim scoring matches the market
+    pub offense_efficiency: f32,
+    // zero for a rating we treat as known
+    pub ratings_sd: TeamRatingsSd,
+
     // filled in by burning in with N sims
     pub prob_1ytg_given_carry: f32,
     pub prob_gz_given_carry: f32,
@@ -119,4 +195,111 @@ impl Team {
 
         teams
     }
+
+    /// the table row this team loads from, so it can be written back out
+    pub fn to_loader(&self) -> TeamLoader {
+        let sd = |sd: f32| (sd > 0.0).then_some(sd);
+        TeamLoader {
+            team: self.team.clone(),
+            pace_z: self.pace_z,
+            offense_proe: self.offense_proe,
+            offense_rz_proe: self.offense_rz_proe,
+            oline_rushing_z: self.oline_rushing_z,
+            offense_pass_rush_z: self.offense_pass_rush_z,
+            defense_proe: self.defense_proe,
+            defense_rz_proe: self.defense_rz_proe,
+            dline_rushing_z: self.dline_rushing_z,
+            defense_pass_rush_z: self.defense_pass_rush_z,
+            defense_completion_z: self.defense_completion_z,
+            defense_interception_z: self.defense_interception_z,
+            defense_yac_oe: self.defense_yac_oe,
+            short_fg_z: self.short_fg_z,
+            long_fg_z: self.long_fg_z,
+            offense_penalty_z: self.offense_penalty_z,
+            defense_penalty_z: self.defense_penalty_z,
+            kickoff_returner_id: self.kickoff_returner_id.clone(),
+            punt_returner_id: self.punt_returner_id.clone(),
+            offense_efficiency: (self.offense_efficiency != 0.0).then_some(self.offense_efficiency),
+            pace_z_sd: sd(self.ratings_sd.pace_z),
+            offense_proe_sd: sd(self.ratings_sd.offense_proe),
+            offense_rz_proe_sd: sd(self.ratings_sd.offense_rz_proe),
+            oline_rushing_z_sd: sd(self.ratings_sd.oline_rushing_z),
+            offense_pass_rush_z_sd: sd(self.ratings_sd.offense_pass_rush_z),
+            defense_proe_sd: sd(self.ratings_sd.defense_proe),
+            defense_rz_proe_sd: sd(self.ratings_sd.defense_rz_proe),
+            dline_rushing_z_sd: sd(self.ratings_sd.dline_rushing_z),
+            defense_pass_rush_z_sd: sd(self.ratings_sd.defense_pass_rush_z),
+            defense_completion_z_sd: sd(self.ratings_sd.defense_completion_z),
+            defense_interception_z_sd: sd(self.ratings_sd.defense_interception_z),
+            defense_yac_oe_sd: sd(self.ratings_sd.defense_yac_oe),
+            short_fg_z_sd: sd(self.ratings_sd.short_fg_z),
+            long_fg_z_sd: sd(self.ratings_sd.long_fg_z),
+            offense_penalty_z_sd: sd(self.ratings_sd.offense_penalty_z),
+            defense_penalty_z_sd: sd(self.ratings_sd.defense_penalty_z),
+        }
+    }
+
+    /// a league-average version of the team: every z-score and over-expectation
+    /// is zeroed, keeping the returners and burned-in red zone rates
+    pub fn neutral(&self) -> Team {
+        Team {
+            team: self.team.clone(),
+            pace_z: 0.0,
+            offense_proe: 0.0,
+            offense_rz_proe: 0.0,
+            oline_rushing_z: 0.0,
+            offense_pass_rush_z: 0.0,
+            defense_proe: 0.0,
+            defense_rz_proe: 0.0,
+            dline_rushing_z: 0.0,
+            defense_pass_rush_z: 0.0,
+            defense_completion_z: 0.0,
+            defense_interception_z: 0.0,
+            defense_yac_oe: 0.0,
+            short_fg_z: 0.0,
+            long_fg_z: 0.0,
+            offense_penalty_z: 0.0,
+            defense_penalty_z: 0.0,
+            kickoff_returner_id: self.kickoff_returner_id.clone(),
+            punt_returner_id: self.punt_returner_id.clone(),
+            offense_efficiency: 0.0,
+            ratings_sd: TeamRatingsSd::default(),
+            prob_1ytg_given_carry: self.prob_1ytg_given_carry,
+            prob_gz_given_carry: self.prob_gz_given_carry,
+            prob_rz_given_target: self.prob_rz_given_target,
+        }
+    }
+
+    /// this sim's ratings, drawn around the point estimates
+    pub fn sample(&self) -> Team {
+        let sd = &self.ratings_sd;
+        let mut team = self.clone();
+        team.pace_z = sample_rating(self.pace_z, sd.pace_z);
+        team.offense_proe = sample_rating(self.offense_proe, sd.offense_proe);
+        team.offense_rz_proe = sample_rating(self.offense_rz_proe, sd.offense_rz_proe);
+        team.oline_rushing_z = sample_rating(self.oline_rushing_z, sd.oline_rushing_z);
+        team.offense_pass_rush_z = sample_rating(self.offense_pass_rush_z, sd.offense_pass_rush_z);
+        team.defense_proe = sample_rating(self.defense_proe, sd.defense_proe);
+        team.defense_rz_proe = sample_rating(self.defense_rz_proe, sd.defense_rz_proe);
+        team.dline_rushing_z = sample_rating(self.dline_rushing_z, sd.dline_rushing_z);
+        team.defense_pass_rush_z = sample_rating(self.defense_pass_rush_z, sd.defense_pass_rush_z);
+        team.defense_completion_z =
+            sample_rating(self.defense_completion_z, sd.defense_completion_z);
+        team.defense_interception_z =
+            sample_rating(self.defense_interception_z, sd.defense_interception_z);
+        team.defense_yac_oe = sample_rating(self.defense_yac_oe, sd.defense_yac_oe);
+        team.short_fg_z = sample_rating(self.short_fg_z, sd.short_fg_z);
+        team.long_fg_z = sample_rating(self.long_fg_z, sd.long_fg_z);
+        team.offense_penalty_z = sample_rating(self.offense_penalty_z, sd.offense_penalty_z);
+        team.defense_penalty_z = sample_rating(self.defense_penalty_z, sd.defense_penalty_z);
+        team
+    }
+
+    /// move the offense with the game's shared environment, where a positive
+    /// environment is a faster, pass heavier and higher scoring game
+    pub fn shift_environment(&mut self, environment: f32) {
+        self.pace_z += PACE_Z_PER_ENVIRONMENT * environment;
+        self.offense_proe += PROE_PER_ENVIRONMENT * environment;
+        self.offense_efficiency += EFFICIENCY_PER_ENVIRONMENT * environment;
+    }
 }
@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+use crate::box_score::drive::{Drive, DriveResult};
+use crate::sim::box_score::BoxScore;
+use crate::start::HomeAway;
+
+/// per-team averages over every simulated drive, handy for sanity-checking
+/// pace and efficiency against real drive charts
+pub fn write_drive_summary(slate_dir: &str, box_scores: &[Vec<BoxScore>]) {
+    let n_sims = box_scores.len() as f32;
+    let mut team_drives: HashMap<String, Vec<&Drive>> = HashMap::new();
+    for bs in box_scores.iter().flatten() {
+        for drive in &bs.drives {
+            let team = match drive.team {
+                HomeAway::Home => &bs.home,
+                HomeAway::Away => &bs.away,
+            };
+            team_drives.entry(team.clone()).or_default().push(drive);
+        }
+    }
+    let mut teams: Vec<&String> = team_drives.keys().collect();
+    teams.sort();
+
+    let proj_path = format!("{}/projections", slate_dir);
+    std::fs::create_dir_all(&proj_path).unwrap();
+    let mut wtr = csv::Writer::from_path(format!("{}/drives.csv", proj_path)).unwrap();
+    let mut header: Vec<String> = [
+        "team",
+        "drives",
+        "start_yardline_100",
+        "plays",
+        "yards",
+        "seconds",
+        "time_of_possession",
+        "points_per_drive",
+    ]
+    .iter()
+    .map(|h| h.to_string())
+    .collect();
+    header.extend(DriveResult::ALL.iter().map(|r| r.to_string()));
+    wtr.write_record(&header).unwrap();
+
+    for team in teams {
+        let drives = &team_drives[team];
+        let n = drives.len() as f32;
+        let mean = |f: &dyn Fn(&Drive) -> f32| drives.iter().map(|d| f(d)).sum::<f32>() / n;
+        let seconds = mean(&|d| d.seconds as f32);
+        let mut record = vec![
+            team.clone(),
+            format!("{:.2}", n / n_sims),
+            format!("{:.1}", mean(&|d| d.start_yards_to_goal as f32)),
+            format!("{:.2}", mean(&|d| d.plays as f32)),
+            format!("{:.1}", mean(&|d| d.yards() as f32)),
+            format!("{:.1}", seconds),
+            // minutes per game
+            format!("{:.1}", seconds * n / n_sims / 60.0),
+            format!("{:.2}", mean(&|d| d.points as f32)),
+        ];
+        record.extend(
+            DriveResult::ALL.iter().map(|result| {
+                format!("{:.3}", mean(&|d| (d.result == Some(*result)) as u8 as f32))
+            }),
+        );
+        wtr.write_record(&record).unwrap();
+    }
+    wtr.flush().unwrap();
+}
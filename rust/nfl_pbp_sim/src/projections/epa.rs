@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use crate::params::GameParamsDistribution;
+use crate::sim::box_score::BoxScore;
+
+#[derive(Default)]
+struct PlayerEpa {
+    dropbacks: f32,
+    dropback_epa: f32,
+    carries: f32,
+    rush_epa: f32,
+    targets: f32,
+    rec_epa: f32,
+}
+
+fn per_play(epa: f32, plays: f32) -> String {
+    match plays > 0.0 {
+        true => format!("{:.3}", epa / plays),
+        false => "".into(),
+    }
+}
+
+/// EPA per dropback, carry and target for every player, with the passing params
+/// alongside so simmed QB efficiency can be checked against them. needs box
+/// scores simmed with an EP table
+pub fn write_player_epa(
+    slate_dir: &str,
+    box_scores: &[Vec<BoxScore>],
+    game_params: &[GameParamsDistribution],
+) {
+    let n_sims = box_scores.len() as f32;
+    let mut totals: HashMap<&String, PlayerEpa> = HashMap::new();
+    for bs in box_scores.iter().flatten() {
+        for (player_id, passing) in &bs.passers {
+            let player = totals.entry(player_id).or_default();
+            player.dropbacks += passing.dropbacks as f32;
+            player.dropback_epa += passing.epa;
+        }
+        for (player_id, skill) in &bs.skill_players {
+            let player = totals.entry(player_id).or_default();
+            player.carries += skill.carries as f32;
+            player.rush_epa += skill.rush_epa;
+            player.targets += skill.targets as f32;
+            player.rec_epa += skill.rec_epa;
+        }
+    }
+
+    let proj_path = format!("{}/projections", slate_dir);
+    std::fs::create_dir_all(&proj_path).unwrap();
+    let mut wtr = csv::Writer::from_path(format!("{}/epa.csv", proj_path)).unwrap();
+    wtr.write_record([
+        "player_id",
+        "name",
+        "team",
+        "cpoe",
+        "ayoe",
+        "dropbacks",
+        "epa_per_dropback",
+        "carries",
+        "epa_per_carry",
+        "targets",
+        "epa_per_target",
+    ])
+    .unwrap();
+
+    for gp in game_params {
+        for team in [&gp.home, &gp.away] {
+            let mut players: Vec<_> = team.skill_players.values().collect();
+            players.sort_by(|a, b| a.player_id.cmp(&b.player_id));
+            for player in players {
+                let epa = match totals.get(&player.player_id) {
+                    Some(epa) => epa,
+                    None => continue,
+                };
+                if epa.dropbacks + epa.carries + epa.targets == 0.0 {
+                    continue;
+                }
+                let qb = team.qbs.iter().find(|qb| qb.player_id == player.player_id);
+                wtr.write_record(&[
+                    player.player_id.clone(),
+                    player.name.clone(),
+                    player.team.clone(),
+                    qb.map_or("".into(), |qb| format!("{:.3}", qb.cpoe)),
+                    qb.map_or("".into(), |qb| format!("{:.3}", qb.ayoe)),
+                    format!("{:.2}", epa.dropbacks / n_sims),
+                    per_play(epa.dropback_epa, epa.dropbacks),
+                    format!("{:.2}", epa.carries / n_sims),
+                    per_play(epa.rush_epa, epa.carries),
+                    format!("{:.2}", epa.targets / n_sims),
+                    per_play(epa.rec_epa, epa.targets),
+                ])
+                .unwrap();
+            }
+        }
+    }
+    wtr.flush().unwrap();
+}
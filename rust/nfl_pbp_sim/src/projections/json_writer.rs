@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use serde::Serialize;
+
+use crate::box_score::skill_player::SkillPlayerProjection;
+use crate::params::{skill_player::Position, PlayerMeta};
+use crate::projections::math::get_projection_items;
+use crate::projections::odds::balanced_line;
+use crate::sim::box_score::{PlayerKey, Projection};
+
+/// what the projections came from, written as the first line of the file
+#[derive(Debug, Clone, Serialize)]
+pub struct RunMetadata {
+    pub slate_id: String,
+    pub n_sims: u32,
+    pub sim_injuries: bool,
+    // seconds since the unix epoch
+    pub generated_at: u64,
+    pub version: String,
+}
+
+impl RunMetadata {
+    pub fn new(slate_id: &str, n_sims: u32, sim_injuries: bool) -> RunMetadata {
+        RunMetadata {
+            slate_id: slate_id.to_string(),
+            n_sims,
+            sim_injuries,
+            generated_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// the balanced half-point (or whole-point, for team lines) line on a stat
+#[derive(Debug, Clone, Serialize)]
+pub struct StatLine {
+    pub stat: &'static str,
+    pub line: f32,
+    pub over_prob: f32,
+}
+
+/// the chance a stat reaches `at_least`
+#[derive(Debug, Clone, Serialize)]
+pub struct Threshold {
+    pub stat: &'static str,
+    pub at_least: i16,
+    pub prob: f32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlayerRecord<'a> {
+    pub key: String,
+    pub name: &'a str,
+    pub team: &'a str,
+    pub opp: &'a str,
+    pub pos: Position,
+    pub projection: &'a Projection,
+    pub lines: Vec<StatLine>,
+    pub thresholds: Vec<Threshold>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub win_prob: Option<f32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum JsonRecord<'a> {
+    Run(&'a RunMetadata),
+    Projection(PlayerRecord<'a>),
+}
+
+/// writes `projections/projections.jsonl`: the run metadata, then one line for
+/// every QB, skill player, team and kicker with its full projection and raw lines
+pub fn write_json_projections(
+    slate_dir: &str,
+    metadata: &RunMetadata,
+    projections: &HashMap<PlayerKey, Projection>,
+    sk_to_player_meta: &HashMap<PlayerKey, PlayerMeta>,
+    opponents: &HashMap<String, String>,
+) {
+    let proj_path = format!("{}/projections", slate_dir);
+    std::fs::create_dir_all(&proj_path).unwrap();
+    let path = format!("{}/projections.jsonl", proj_path);
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&path).unwrap());
+
+    write_record(&mut file, &JsonRecord::Run(metadata));
+    let items = get_projection_items(projections, sk_to_player_meta, opponents, true);
+    for (sk, proj) in &items {
+        let meta = &sk_to_player_meta[sk];
+        let (lines, thresholds, win_prob) = match proj {
+            Projection::Qb(qb_proj) => {
+                let passing = &qb_proj.passing;
+                let (mut lines, mut thresholds) = skill_lines(&qb_proj.skill);
+                lines.insert(0, stat_line("passing_yards", &passing.yards_sampled));
+                lines.insert(1, stat_line("passing_tds", &passing.tds_sampled));
+                thresholds.insert(0, threshold("passing_yards", &passing.yards_sampled, 300));
+                (lines, thresholds, None)
+            }
+            Projection::Skill(skill_proj) => {
+                let (lines, thresholds) = skill_lines(&skill_proj.skill);
+                (lines, thresholds, None)
+            }
+            Projection::Team(team_proj) => {
+                let opp_sk = PlayerKey::TeamPos(opponents[&meta.team].clone(), Position::Defense);
+                match &projections[&opp_sk] {
+                    Projection::Team(opp_proj) => {
+                        let (lines, win_prob) = team_lines(
+                            &team_proj.offense.points_sampled,
+                            &opp_proj.offense.points_sampled,
+                        );
+                        (lines, vec![], Some(win_prob))
+                    }
+                    _ => (vec![], vec![], None),
+                }
+            }
+            Projection::Kicker(_) => (vec![], vec![], None),
+        };
+        let record = PlayerRecord {
+            key: sk.to_string(),
+            name: &meta.name,
+            team: &meta.team,
+            opp: &meta.opp,
+            pos: meta.pos,
+            projection: proj,
+            lines,
+            thresholds,
+            win_prob,
+        };
+        write_record(&mut file, &JsonRecord::Projection(record));
+    }
+    file.flush().unwrap();
+    log::info!("wrote {} projections to {}", items.len(), path);
+}
+
+fn write_record(file: &mut impl Write, record: &JsonRecord) {
+    serde_json::to_writer(&mut *file, record).unwrap();
+    file.write_all(b"\n").unwrap();
+}
+
+fn stat_line<T: Into<i16> + Copy>(stat: &'static str, sampled: &Vec<T>) -> StatLine {
+    let (line, over_prob) = balanced_line(sampled, true);
+    StatLine {
+        stat,
+        line,
+        over_prob,
+    }
+}
+
+fn threshold<T: Into<i16> + Copy>(stat: &'static str, sampled: &[T], at_least: i16) -> Threshold {
+    let hits = sampled.iter().filter(|v| (**v).into() >= at_least).count();
+    Threshold {
+        stat,
+        at_least,
+        prob: hits as f32 / sampled.len().max(1) as f32,
+    }
+}
+
+fn skill_lines(skill: &SkillPlayerProjection) -> (Vec<StatLine>, Vec<Threshold>) {
+    let lines = vec![
+        stat_line("rushing_yards", &skill.rushing_yards_sampled),
+        stat_line("receiving_yards", &skill.receiving_yards_sampled),
+        stat_line("total_yards", &skill.total_yards_sampled),
+    ];
+    let thresholds = vec![
+        threshold("rushing_tds", &skill.rushing_tds_sampled, 1),
+        threshold("receiving_tds", &skill.receiving_tds_sampled, 1),
+        threshold("total_tds", &skill.total_tds_sampled, 1),
+        threshold("rushing_yards", &skill.rushing_yards_sampled, 100),
+        threshold("receiving_yards", &skill.receiving_yards_sampled, 100),
+    ];
+    (lines, thresholds)
+}
+
+/// the team's margin and game total lines, and its win probability with ties as half a win
+fn team_lines(points: &[u8], opp_points: &[u8]) -> (Vec<StatLine>, f32) {
+    let (mut margins, mut totals, mut wins) = (vec![], vec![], 0.0);
+    for (pts, opp_pts) in points.iter().zip(opp_points) {
+        margins.push(*pts as i16 - *opp_pts as i16);
+        totals.push(*pts as i16 + *opp_pts as i16);
+        wins += match pts.cmp(opp_pts) {
+            std::cmp::Ordering::Greater => 1.0,
+            std::cmp::Ordering::Equal => 0.5,
+            std::cmp::Ordering::Less => 0.0,
+        };
+    }
+    let (margin, margin_over_prob) = balanced_line(&margins, false);
+    let (total, total_over_prob) = balanced_line(&totals, false);
+    let lines = vec![
+        StatLine {
+            stat: "margin",
+            line: margin,
+            over_prob: margin_over_prob,
+        },
+        StatLine {
+            stat: "total",
+            line: total,
+            over_prob: total_over_prob,
+        },
+    ];
+    (lines, wins / points.len().max(1) as f32)
+}
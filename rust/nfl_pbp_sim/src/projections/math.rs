@@ -115,6 +115,7 @@ fn update_projections(
             if let Projection::Team(team_proj) = proj {
                 team_proj.defense.add(points, team_proj.n_sims);
                 team_proj.offense.add(team, box_score, team_proj.n_sims);
+                team_proj.drives.add(team, box_score, team_proj.n_sims);
                 team_proj.update_field_position(box_score.field_position.get(team).unwrap());
                 team_proj
                     .special_teams
@@ -1,3 +1,6 @@
+pub mod drives;
+pub mod epa;
+pub mod json_writer;
 pub mod math;
 pub mod odds;
 pub mod writer;
@@ -10,6 +10,20 @@ pub fn probability_to_american_odds(p: f32) -> String {
     }
 }
 
+pub fn american_odds_to_probability(odds: f32) -> f32 {
+    match odds < 0.0 {
+        true => -odds / (100.0 - odds),
+        false => 100.0 / (100.0 + odds),
+    }
+}
+
+pub fn probability_to_decimal_odds(p: f32) -> String {
+    if (p <= 0.0) | (p >= 1.0) {
+        return "N/A".to_string();
+    }
+    format!("{:.3}", 1.0 / p)
+}
+
 pub fn balanced_line<T: Into<i16> + Copy>(values: &Vec<T>, only_halves: bool) -> (f32, f32) {
     // log::info!("median = {}", values[values.len() / 2]);
     let mut cumsum: f32 = 0.0;
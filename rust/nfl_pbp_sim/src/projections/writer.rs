@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use crate::box_score::defense::POINTS_ALLOWED_TIERS;
 use crate::params::{skill_player::Position, PlayerMeta};
 use crate::projections::math::get_projection_items;
 use crate::projections::odds::{balanced_line, make_yards_line, probability_to_american_odds};
@@ -42,7 +43,9 @@ impl ProjectionsWriter {
         self.write_skill_projections(&mut items);
 
         self.write_team_projections(&mut items);
-        // self.write_field_positions(&mut items);
+        self.write_kicking_projections(&items);
+        self.write_dst_projections(&items);
+        self.write_field_positions(&items);
     }
 
     fn write_qb_projections(&self, projections: &mut Vec<(PlayerKey, Projection)>) {
@@ -250,6 +253,12 @@ impl ProjectionsWriter {
             "op_yards",
             "defensive_penalties",
             "dp_yards",
+            "drives",
+            "points_per_drive",
+            "td_rate",
+            "rz_trips",
+            "rz_td_rate",
+            "three_and_out_rate",
         ])
         .unwrap();
         for (sk, proj) in projections {
@@ -258,6 +267,7 @@ impl ProjectionsWriter {
                 let defense = &team_proj.defense;
                 let offense = &team_proj.offense;
                 let special_teams = &team_proj.special_teams;
+                let drives = &team_proj.drives;
                 let opp_team_sk = PlayerKey::TeamPos(
                     self.opponents[&sk.expect_team()].clone(),
                     Position::Defense,
@@ -317,6 +327,12 @@ impl ProjectionsWriter {
                         format!("{:.1}", offense.penalty_yards),
                         format!("{:.2}", defense.penalties),
                         format!("{:.1}", defense.penalty_yards),
+                        format!("{:.1}", drives.drives),
+                        format!("{:.2}", drives.points_per_drive()),
+                        format!("{:.3}", drives.touchdown_rate()),
+                        format!("{:.2}", drives.red_zone_trips),
+                        format!("{:.3}", drives.red_zone_touchdown_rate()),
+                        format!("{:.3}", drives.three_and_out_rate()),
                     ])
                     .unwrap();
                 }
@@ -326,10 +342,129 @@ impl ProjectionsWriter {
         wtr.flush().unwrap();
     }
 
+    fn write_kicking_projections(&self, projections: &Vec<(PlayerKey, Projection)>) {
+        let mut wtr =
+            csv::Writer::from_path(format!("{}/projections/kicking.csv", self.slate_dir)).unwrap();
+        wtr.write_record([
+            "id",
+            "name",
+            "team",
+            "fga",
+            "fg",
+            "fga_0-29",
+            "fg_0-29",
+            "fga_30-39",
+            "fg_30-39",
+            "fga_40-49",
+            "fg_40-49",
+            "fga_50+",
+            "fg_50+",
+            "pat_att",
+            "pat",
+            "dk_points",
+            "fd_points",
+            "standard_points",
+        ])
+        .unwrap();
+
+        for (sk, proj) in projections {
+            if let Projection::Kicker(kicking_proj) = proj {
+                let player_meta = self.sk_to_player_meta.get(sk).unwrap();
+                let kicking = &kicking_proj.kicking;
+                let points = &kicking_proj.points;
+                wtr.write_record(&[
+                    sk.to_string(),
+                    player_meta.name.clone(),
+                    player_meta.team.clone(),
+                    format!("{:.2}", kicking.fgs_attempted),
+                    format!("{:.2}", kicking.fgs_made),
+                    format!("{:.2}", kicking.fg_att_u30),
+                    format!("{:.2}", kicking.fg_made_u30),
+                    format!("{:.2}", kicking.fg_att_30_39),
+                    format!("{:.2}", kicking.fg_made_30_39),
+                    format!("{:.2}", kicking.fg_att_40_49),
+                    format!("{:.2}", kicking.fg_made_40_49),
+                    format!("{:.2}", kicking.fg_att_50o),
+                    format!("{:.2}", kicking.fg_made_50o),
+                    format!("{:.2}", kicking.pats_attempted),
+                    format!("{:.2}", kicking.pats_made),
+                    format!("{:.2}", points.draftkings),
+                    format!("{:.2}", points.fanduel),
+                    format!("{:.2}", points.standard),
+                ])
+                .unwrap();
+            }
+        }
+        wtr.flush().unwrap();
+    }
+
+    fn write_dst_projections(&self, projections: &Vec<(PlayerKey, Projection)>) {
+        let mut wtr =
+            csv::Writer::from_path(format!("{}/projections/dst.csv", self.slate_dir)).unwrap();
+        let mut header: Vec<String> = [
+            "id",
+            "name",
+            "team",
+            "sacks",
+            "interceptions",
+            "fumble_recoveries",
+            "defensive_tds",
+            "safeties",
+            "blocked_kicks",
+            "points_allowed",
+        ]
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+        for (min, max, _) in POINTS_ALLOWED_TIERS {
+            header.push(match max {
+                u8::MAX => format!("P(PA {}+)", min),
+                _ if min == max => format!("P(PA {})", min),
+                _ => format!("P(PA {}-{})", min, max),
+            });
+        }
+        header.extend(["dk_points", "fd_points", "standard_points"].map(String::from));
+        wtr.write_record(&header).unwrap();
+
+        for (sk, proj) in projections {
+            if let Projection::Team(team_proj) = proj {
+                let player_meta = self.sk_to_player_meta.get(sk).unwrap();
+                let defense = &team_proj.defense;
+                let points = &team_proj.points;
+                let mut record = vec![
+                    sk.to_string(),
+                    player_meta.name.clone(),
+                    player_meta.team.clone(),
+                    format!("{:.2}", defense.sacks),
+                    format!("{:.2}", defense.interceptions),
+                    format!("{:.2}", defense.fumble_recoveries),
+                    format!("{:.2}", defense.touchdowns),
+                    format!("{:.2}", defense.safeties),
+                    format!("{:.2}", defense.blocked_kicks),
+                    format!("{:.1}", defense.points_allowed),
+                ];
+                let n_sims = defense.points_allowed_sampled.len().max(1) as f32;
+                for (min, max, _) in POINTS_ALLOWED_TIERS {
+                    let in_tier = defense
+                        .points_allowed_sampled
+                        .iter()
+                        .filter(|pa| (min..=max).contains(*pa))
+                        .count();
+                    record.push(format!("{:.3}", in_tier as f32 / n_sims));
+                }
+                record.push(format!("{:.2}", points.draftkings));
+                record.push(format!("{:.2}", points.fanduel));
+                record.push(format!("{:.2}", points.standard));
+                wtr.write_record(&record).unwrap();
+            }
+        }
+        wtr.flush().unwrap();
+    }
+
     /// Mainly used to debug whether we have the right distribution of plays called from each yardline
     /// This was particularly useful to learn that modeling pass interference in the endzone
     /// was critical for getting an accurate rushTD/passTD/FG ratio
-    fn _write_field_positions(&self, projections: &Vec<(PlayerKey, Projection)>) {
+    fn write_field_positions(&self, projections: &Vec<(PlayerKey, Projection)>) {
         let mut team_projs = HashMap::new();
         for (sk, proj) in projections {
             if let Projection::Team(team_proj) = proj {
@@ -1,13 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Serialize, Serializer};
 
 use crate::{
     box_score::{
         defense::{DefenseBoxScore, DefenseProjection, OffenseProjection, SpecialTeamsProjection},
+        drive::{Drive, DriveProjection, DriveResult},
         kicking::{KickerBoxScore, KickerProjection},
         passing::{PassingBoxScore, PassingProjection},
         skill_player::{SkillPlayerBoxScore, SkillPlayerProjection},
     },
     params::{skill_player::Position, GameParams},
+    sim::expected_points::PlayValue,
     sim::play_result::{
         DropbackOutcome, DropbackResult, FieldGoalResult, KickingResult, PATDropbackOutcome,
         PATKickingOutcome, PATResult, PATRushingOutcome, PenaltyType, PlayResult, RunResult,
@@ -15,13 +19,14 @@ use crate::{
     },
     start::HomeAway,
     state::{
+        clock::{GameClock, Quarter},
         down::PlayState,
         game_state::{Plays, Score},
         yards_to_goal::YardsToGoal,
     },
 };
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct PlayerFantasyPoints {
     pub draftkings: f32,
     pub fanduel: f32,
@@ -78,6 +83,26 @@ impl FantasyPoints {
         }
         combined_fp
     }
+
+    pub fn by_player_key(&self) -> HashMap<PlayerKey, PlayerFantasyPoints> {
+        let mut points = HashMap::new();
+        for (player_id, pfp) in &self.players {
+            points.insert(PlayerKey::NflId(player_id.clone()), pfp.clone());
+        }
+        for (team, dfp) in &self.defenses {
+            points.insert(
+                PlayerKey::TeamPos(team.clone(), Position::Defense),
+                dfp.clone(),
+            );
+        }
+        for (team, kfp) in &self.kickers {
+            points.insert(
+                PlayerKey::TeamPos(team.clone(), Position::Kicker),
+                kfp.clone(),
+            );
+        }
+        points
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -93,6 +118,22 @@ pub struct BoxScore {
     pub field_position: HashMap<String, HashMap<u8, u16>>,
     pub home: String,
     pub away: String,
+    pub scoring_plays: Vec<ScoringPlay>,
+    // period of the play currently being applied
+    pub quarter: Quarter,
+    pub drives: Vec<Drive>,
+    // one per snap from scrimmage, empty unless simmed with an EP table
+    pub play_values: Vec<PlayValue>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScoringPlay {
+    pub quarter: Quarter,
+    pub team: HomeAway,
+    pub points: u8,
+    pub touchdown: bool,
+    // player who scored a touchdown, none for a D/ST score without a returner
+    pub scorer: Option<String>,
 }
 
 impl BoxScore {
@@ -110,9 +151,27 @@ impl BoxScore {
             field_position,
             home,
             away,
+            scoring_plays: vec![],
+            quarter: Quarter::First,
+            drives: vec![],
+            play_values: vec![],
         }
     }
 
+    /// points each team scored across the given periods
+    pub fn period_score(&self, quarters: &[Quarter]) -> Score {
+        let mut score = Score::new();
+        for sp in &self.scoring_plays {
+            if quarters.contains(&sp.quarter) {
+                match sp.team {
+                    HomeAway::Home => score.home += sp.points,
+                    HomeAway::Away => score.away += sp.points,
+                }
+            }
+        }
+        score
+    }
+
     pub fn fantasy_points(&self) -> FantasyPoints {
         let mut fp = FantasyPoints {
             players: HashMap::new(),
@@ -241,6 +300,13 @@ impl BoxScore {
                 self.score.home += points;
             }
         }
+        self.scoring_plays.push(ScoringPlay {
+            quarter: self.quarter,
+            team: *home_away,
+            points,
+            touchdown: false,
+            scorer: None,
+        });
         if against_defense {
             let dst = self
                 .defenses
@@ -250,6 +316,107 @@ impl BoxScore {
         }
     }
 
+    pub fn add_touchdown(
+        &mut self,
+        home_away: &HomeAway,
+        against_defense: bool,
+        scorer: Option<&String>,
+    ) {
+        self.add_points(6, home_away, against_defense);
+        let scoring_play = self.scoring_plays.last_mut().unwrap();
+        scoring_play.touchdown = true;
+        scoring_play.scorer = scorer.cloned();
+    }
+
+    fn open_drive(&mut self) -> Option<&mut Drive> {
+        self.drives
+            .last_mut()
+            .filter(|drive| drive.result.is_none())
+    }
+
+    /// open a drive on the first snap of a new possession
+    pub fn start_drive(&mut self, state: &PlayState, clock: &GameClock) {
+        if let PlayState::Down(down_to_go) = state {
+            if let Some(drive) = self.open_drive() {
+                if drive.team == down_to_go.possession {
+                    return;
+                }
+                drive.result = Some(DriveResult::Turnover);
+            }
+            self.drives.push(Drive::new(down_to_go, clock));
+        }
+    }
+
+    /// `points` are what the team with the ball scored on the play
+    pub fn apply_drive(
+        &mut self,
+        play: &PlayResult,
+        prev_state: &PlayState,
+        next_state: &PlayState,
+        points: u8,
+    ) {
+        match prev_state {
+            PlayState::Down(_) => {
+                if let Some(drive) = self.open_drive() {
+                    drive.add_play(play, next_state, points);
+                }
+            }
+            PlayState::PointAfterTouchdown(team) => {
+                if let Some(drive) = self.drives.last_mut() {
+                    if drive.team == *team && drive.result == Some(DriveResult::Touchdown) {
+                        drive.points += points;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn add_drive_seconds(&mut self, seconds: u16) {
+        if let Some(drive) = self.drives.last_mut() {
+            drive.seconds += seconds;
+        }
+    }
+
+    pub fn end_drive(&mut self, result: DriveResult) {
+        if let Some(drive) = self.open_drive() {
+            drive.result = Some(result);
+        }
+    }
+
+    /// log a valued snap and credit its EPA to the passer, ball carrier and target
+    pub fn apply_play_value(&mut self, play: &PlayResult, value: PlayValue) {
+        let epa = value.epa;
+        self.play_values.push(value);
+        let (passer_id, carrier_id, receiver_id) = match play {
+            PlayResult::Dropback(dropback_result) => match &dropback_result.outcome {
+                // a scramble counts as a carry, so it's credited to the rush alone
+                DropbackOutcome::QbScramble(run_result) => {
+                    (None, Some(&run_result.carrier_id), None)
+                }
+                DropbackOutcome::Target(target_result) => (
+                    Some(&dropback_result.passer_id),
+                    None,
+                    Some(&target_result.targeted_receiver_id),
+                ),
+                _ => (Some(&dropback_result.passer_id), None, None),
+            },
+            PlayResult::DesignedRun(run_result) | PlayResult::QbKneel(run_result) => {
+                (None, Some(&run_result.carrier_id), None)
+            }
+            _ => (None, None, None),
+        };
+        if let Some(box_score) = passer_id.and_then(|id| self.passers.get_mut(id)) {
+            box_score.add_dropback_epa(epa);
+        }
+        if let Some(box_score) = carrier_id.and_then(|id| self.skill_players.get_mut(id)) {
+            box_score.add_rush_epa(epa);
+        }
+        if let Some(box_score) = receiver_id.and_then(|id| self.skill_players.get_mut(id)) {
+            box_score.add_rec_epa(epa);
+        }
+    }
+
     fn team_name(&self, home_away: HomeAway) -> String {
         match home_away {
             HomeAway::Away => self.away.clone(),
@@ -298,15 +465,32 @@ impl BoxScore {
         }
     }
 
-    pub fn apply_stats(&mut self, play: &PlayResult, prev_state: &PlayState) {
+    pub fn apply_stats(&mut self, play: &PlayResult, prev_state: &PlayState, quarter: Quarter) {
+        self.quarter = quarter;
+        if let PlayResult::PointAfterTouchdown(
+            PATResult::TwoPointDesignedRun(_) | PATResult::TwoPointDropback(_),
+        ) = play
+        {
+            self.add_two_point_attempt(prev_state.possession());
+        }
         match play {
             PlayResult::Kickoff(returner_id, outcome) => {
+                let team = self.team_name(prev_state.kicking_team());
+                let box_score = self.kickers.get_mut(&team).unwrap();
+                box_score.add_kickoff(matches!(
+                    outcome,
+                    KickingResult::OnsideRecovery(_) | KickingResult::OnsideNotRecovered(_)
+                ));
+
                 self.apply_return_stats(returner_id, outcome, prev_state);
             }
             PlayResult::FieldGoal(fg_result) => match fg_result {
                 FieldGoalResult::AttemptedFg(made) => self.apply_fg_stats(*made, prev_state),
                 FieldGoalResult::Blocked(turnover_outcome) => {
-                    self.apply_kick_block(turnover_outcome, prev_state.defense())
+                    self.apply_kick_block(turnover_outcome, prev_state.defense());
+                    if *turnover_outcome == TurnoverOutcome::Touchdown {
+                        self.add_touchdown(&prev_state.defense(), false, None);
+                    }
                 }
             },
             PlayResult::Punt(returner_id, outcome) => {
@@ -317,7 +501,10 @@ impl BoxScore {
                 self.apply_return_stats(returner_id, outcome, prev_state);
                 match outcome {
                     KickingResult::Blocked(turnover_outcome) => {
-                        self.apply_kick_block(turnover_outcome, prev_state.defense())
+                        self.apply_kick_block(turnover_outcome, prev_state.defense());
+                        if *turnover_outcome == TurnoverOutcome::Touchdown {
+                            self.add_touchdown(&prev_state.defense(), false, None);
+                        }
                     }
                     _ => {}
                 }
@@ -441,11 +628,14 @@ impl BoxScore {
         }
         match run_result.outcome {
             RushingOutcome::Touchdown => {
-                self.add_points(6, &prev_state.possession(), true);
+                self.add_touchdown(&prev_state.possession(), true, Some(&run_result.carrier_id));
             }
             RushingOutcome::Safety => {
                 self.add_points(2, &prev_state.possession().flip(), false);
             }
+            RushingOutcome::FumbleLost(_, TurnoverOutcome::Touchdown) => {
+                self.add_touchdown(&prev_state.defense(), false, None);
+            }
             _ => {}
         }
     }
@@ -455,6 +645,11 @@ impl BoxScore {
         box_score.add_pat_attempt(is_good);
     }
 
+    fn add_two_point_attempt(&mut self, team: HomeAway) {
+        let box_score = self.kickers.get_mut(&self.team_name(team)).unwrap();
+        box_score.add_two_point_attempt();
+    }
+
     fn apply_pass_stats(&mut self, dropback_result: &DropbackResult, prev_state: &PlayState) {
         let is_target = match &dropback_result.outcome {
             DropbackOutcome::Target(_) => true,
@@ -473,7 +668,7 @@ impl BoxScore {
             DropbackOutcome::Sack(SackOutcome::Safety) => {
                 self.add_points(2, &prev_state.possession().flip(), false);
             }
-            DropbackOutcome::Sack(SackOutcome::FumbleLost(_)) => {
+            DropbackOutcome::Sack(SackOutcome::FumbleLost(turnover_outcome)) => {
                 match self.skill_players.get_mut(&dropback_result.passer_id) {
                     Some(box_score) => box_score.add_fumble_lost(),
                     None => {
@@ -483,13 +678,21 @@ impl BoxScore {
                             .insert(dropback_result.passer_id.clone(), box_score);
                     }
                 }
+                if *turnover_outcome == TurnoverOutcome::Touchdown {
+                    self.add_touchdown(&prev_state.defense(), false, None);
+                }
             }
             DropbackOutcome::Target(target_result) => match &target_result.outcome {
                 TargetOutcome::Touchdown(_) => {
-                    self.add_points(6, &prev_state.possession(), true);
+                    self.add_touchdown(
+                        &prev_state.possession(),
+                        true,
+                        Some(&target_result.targeted_receiver_id),
+                    );
                 }
-                TargetOutcome::Interception(_, TurnoverOutcome::Touchdown) => {
-                    self.add_points(6, &prev_state.defense(), false);
+                TargetOutcome::Interception(_, TurnoverOutcome::Touchdown)
+                | TargetOutcome::CatchThenFumble(_, TurnoverOutcome::Touchdown) => {
+                    self.add_touchdown(&prev_state.defense(), false, None);
                 }
                 _ => {}
             },
@@ -642,11 +845,15 @@ impl BoxScore {
                     returner_id_opt,
                     &self.team_name(prev_state.returning_team()),
                 );
-                self.add_points(6, &prev_state.kicking_team().flip(), true);
+                self.add_touchdown(
+                    &prev_state.kicking_team().flip(),
+                    true,
+                    returner_id_opt.as_ref(),
+                );
             }
             KickingResult::FumbleLost(turnover_outcome) => {
                 if *turnover_outcome == TurnoverOutcome::Touchdown {
-                    self.add_points(6, &prev_state.kicking_team(), true);
+                    self.add_touchdown(&prev_state.kicking_team(), true, None);
                 }
                 let def_box_score = self
                     .defenses
@@ -668,6 +875,7 @@ impl BoxScore {
             KickingResult::PuntTouchback => {}
             KickingResult::KickoffTouchback => {}
             KickingResult::OnsideRecovery(_) => {}
+            KickingResult::OnsideNotRecovered(_) => {}
             KickingResult::Blocked(turnover_outcome) => {
                 let def_box_score = self
                     .defenses
@@ -691,7 +899,7 @@ impl BoxScore {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct QbProjection {
     pub n_sims: f32,
     pub points: PlayerFantasyPoints,
@@ -699,23 +907,33 @@ pub struct QbProjection {
     pub skill: SkillPlayerProjection,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct SkillProjection {
     pub n_sims: f32,
     pub points: PlayerFantasyPoints,
     pub skill: SkillPlayerProjection,
 }
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct TeamProjection {
     pub n_sims: f32,
     pub points: PlayerFantasyPoints,
     pub defense: DefenseProjection,
     pub offense: OffenseProjection,
     pub special_teams: SpecialTeamsProjection,
+    pub drives: DriveProjection,
+    // plays called from each yardline, summed over every sim
+    #[serde(serialize_with = "serialize_sorted")]
     pub field_position: HashMap<u8, u64>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+fn serialize_sorted<S: Serializer>(
+    map: &HashMap<u8, u64>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(map.iter().collect::<BTreeMap<_, _>>())
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct KickingProjection {
     pub n_sims: f32,
     pub points: PlayerFantasyPoints,
@@ -742,9 +960,19 @@ impl PlayerKey {
             PlayerKey::TeamPos(team, pos) => format!("{}-{}", team, pos.to_string()),
         }
     }
+
+    /// inverse of `to_string`: "GB-DEF" and "GB-K" are team positions, anything else is an NFL id
+    pub fn from_string(key: &str) -> PlayerKey {
+        match key.rsplit_once('-') {
+            Some((team, "DEF")) => PlayerKey::TeamPos(team.to_string(), Position::Defense),
+            Some((team, "K")) => PlayerKey::TeamPos(team.to_string(), Position::Kicker),
+            _ => PlayerKey::NflId(key.to_string()),
+        }
+    }
 }
 
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum Projection {
     Qb(QbProjection),
     Skill(SkillProjection),
@@ -834,9 +1062,14 @@ impl KickingProjection {
                 _ => kicking.fg_made_50o += 1.0 / self.n_sims,
             };
             kicking.fgs_made += 1.0 / self.n_sims;
-            kicking.fgs_attempted += 1.0 / self.n_sims;
         }
-        for _ in &stats.fgs_missed {
+        for fg_distance in stats.fgs_made.iter().chain(&stats.fgs_missed) {
+            match fg_distance {
+                0..=29 => kicking.fg_att_u30 += 1.0 / self.n_sims,
+                30..=39 => kicking.fg_att_30_39 += 1.0 / self.n_sims,
+                40..=49 => kicking.fg_att_40_49 += 1.0 / self.n_sims,
+                _ => kicking.fg_att_50o += 1.0 / self.n_sims,
+            };
             kicking.fgs_attempted += 1.0 / self.n_sims;
         }
         for _ in 0..stats.num_punts {
@@ -853,6 +1086,7 @@ impl TeamProjection {
             defense: DefenseProjection::new(),
             offense: OffenseProjection::new(),
             special_teams: SpecialTeamsProjection::new(),
+            drives: DriveProjection::new(),
             field_position: HashMap::new(),
         }
     }
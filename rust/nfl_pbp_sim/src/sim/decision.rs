@@ -0,0 +1,159 @@
+use progress_bar::*;
+use rayon::prelude::*;
+
+use crate::{
+    game_loop::field_goals::fg_distance,
+    params::GameParamsDistribution,
+    sim::{play_out, GameSim},
+    start::HomeAway,
+    state::{
+        down::{Down, PlayState},
+        game_state::GameState,
+    },
+    util::clock::mins_secs,
+};
+
+// z for a two-sided 95% interval
+const Z_95: f32 = 1.96;
+
+/// a choice the advisor can force on the first snap. everything after that is
+/// left to the models
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    GoForIt,
+    Punt,
+    FieldGoal,
+    KickPat,
+    GoForTwo,
+}
+
+impl std::fmt::Display for Decision {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Decision::GoForIt => write!(f, "go_for_it"),
+            Decision::Punt => write!(f, "punt"),
+            Decision::FieldGoal => write!(f, "field_goal"),
+            Decision::KickPat => write!(f, "kick_pat"),
+            Decision::GoForTwo => write!(f, "go_for_two"),
+        }
+    }
+}
+
+impl Decision {
+    /// the choices open to the offense on a 4th down or a try
+    pub fn options(play: &PlayState) -> Vec<Decision> {
+        match play {
+            PlayState::Down(dtg) if dtg.down == Down::Fourth => {
+                let mut options = vec![Decision::GoForIt, Decision::Punt];
+                // the playcall model never tries one from beyond 70 yards either
+                if fg_distance(dtg.yards_to_goal.0) <= 70 {
+                    options.push(Decision::FieldGoal);
+                }
+                options
+            }
+            PlayState::PointAfterTouchdown(_) => vec![Decision::KickPat, Decision::GoForTwo],
+            _ => panic!("no decision to make on {}", play),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DecisionOutcome {
+    pub decision: Decision,
+    pub n_sims: u32,
+    // from the deciding team's side, ties count as half a win
+    pub win_prob: f32,
+    pub win_prob_lo: f32,
+    pub win_prob_hi: f32,
+    pub mean_margin: f32,
+}
+
+/// wilson score interval, which behaves near 0 and 1 where the normal one doesn't
+fn wilson_interval(p: f32, n: f32) -> (f32, f32) {
+    let z2 = Z_95 * Z_95;
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let half_width = Z_95 / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    (
+        (center - half_width).max(0.0),
+        (center + half_width).min(1.0),
+    )
+}
+
+/// sim the rest of the game `n_sims` times for each option, forcing only the
+/// first decision
+pub fn advise(
+    n_sims: u32,
+    game_params: &GameParamsDistribution,
+    game_state: &GameState,
+    kicks_h2_start: HomeAway,
+) -> Vec<DecisionOutcome> {
+    let team = game_state.play.possession();
+    let options = Decision::options(&game_state.play);
+    init_progress_bar(options.len() * n_sims as usize);
+    enable_eta();
+    set_progress_bar_action("Decisions", Color::Green, Style::Normal);
+    let start_time = std::time::Instant::now();
+
+    let outcomes = options
+        .iter()
+        .map(|decision| {
+            let margins: Vec<f32> = (0..n_sims)
+                .into_par_iter()
+                .map(|_| {
+                    let sim = GameSim::new(
+                        game_params.to_game_params().injuries(false),
+                        game_state.clone(),
+                    )
+                    .with_forced_decision(*decision);
+                    let box_score = play_out(sim, kicks_h2_start);
+                    inc_progress_bar();
+                    box_score.score.margin(team) as f32
+                })
+                .collect();
+            let n = n_sims as f32;
+            let win_prob = margins
+                .iter()
+                .map(|m| match m.partial_cmp(&0.0).unwrap() {
+                    std::cmp::Ordering::Greater => 1.0,
+                    std::cmp::Ordering::Equal => 0.5,
+                    std::cmp::Ordering::Less => 0.0,
+                })
+                .sum::<f32>()
+                / n;
+            let (win_prob_lo, win_prob_hi) = wilson_interval(win_prob, n);
+            DecisionOutcome {
+                decision: *decision,
+                n_sims,
+                win_prob,
+                win_prob_lo,
+                win_prob_hi,
+                mean_margin: margins.iter().sum::<f32>() / n,
+            }
+        })
+        .collect();
+
+    finalize_progress_bar();
+    let (mins, secs) = mins_secs(std::time::Instant::now() - start_time);
+    log::info!("Simmed decisions in {:.0}m {:.0}s", mins, secs);
+    outcomes
+}
+
+pub fn write_decisions(slate_dir: &str, outcomes: &[DecisionOutcome]) {
+    let proj_path = format!("{}/projections", slate_dir);
+    std::fs::create_dir_all(&proj_path).unwrap();
+    let mut wtr = csv::Writer::from_path(format!("{}/decisions.csv", proj_path)).unwrap();
+    wtr.write_record(["decision", "n_sims", "wp", "wp_lo", "wp_hi", "mean_margin"])
+        .unwrap();
+    for outcome in outcomes {
+        wtr.write_record(&[
+            outcome.decision.to_string(),
+            outcome.n_sims.to_string(),
+            format!("{:.4}", outcome.win_prob),
+            format!("{:.4}", outcome.win_prob_lo),
+            format!("{:.4}", outcome.win_prob_hi),
+            format!("{:.2}", outcome.mean_margin),
+        ])
+        .unwrap();
+    }
+    wtr.flush().unwrap();
+}
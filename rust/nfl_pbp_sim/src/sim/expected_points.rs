@@ -0,0 +1,581 @@
+use std::collections::HashMap;
+
+use csv::Reader;
+use progress_bar::*;
+use rayon::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    params::GameParamsDistribution,
+    sim::{box_score::BoxScore, sim_game_from_state},
+    start::HomeAway,
+    state::{
+        clock::{GameClock, Quarter},
+        down::{Down, DownToGo, PlayState, ToGo},
+        game_state::{GameState, Score, TimeoutsUsed},
+        yards_to_goal::YardsToGoal,
+    },
+    util::{clock::mins_secs, stats::random_bool},
+};
+
+// nflfastR values each kind of next score the same way
+const TD_VALUE: f32 = 7.0;
+const FG_VALUE: f32 = 3.0;
+const SAFETY_VALUE: f32 = 2.0;
+
+/// every combination of these gets its own row. states where the distance
+/// is longer than the field are skipped, and distance == yardline is goal to go
+#[derive(Debug, Clone)]
+pub struct StateGrid {
+    pub downs: Vec<u8>,
+    pub distances: Vec<u8>,
+    pub yardlines: Vec<u8>,
+    // from the offense's side
+    pub score_diffs: Vec<i8>,
+    // game seconds remaining in regulation
+    pub seconds_remaining: Vec<u16>,
+    // each team has this many left
+    pub timeouts: Vec<u8>,
+}
+
+impl Default for StateGrid {
+    fn default() -> StateGrid {
+        StateGrid {
+            downs: vec![1, 2, 3, 4],
+            distances: vec![2, 5, 10],
+            yardlines: vec![10, 25, 50, 75, 90],
+            score_diffs: vec![-7, 0, 7],
+            seconds_remaining: vec![3600, 1800, 600, 120],
+            timeouts: vec![3],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridState {
+    pub down: u8,
+    pub distance: u8,
+    pub yardline: u8,
+    pub score_diff: i8,
+    pub seconds_remaining: u16,
+    pub timeouts: u8,
+}
+
+impl StateGrid {
+    pub fn states(&self) -> Vec<GridState> {
+        let mut states = vec![];
+        for &down in &self.downs {
+            for &distance in &self.distances {
+                for &yardline in &self.yardlines {
+                    if distance > yardline {
+                        continue;
+                    }
+                    for &score_diff in &self.score_diffs {
+                        for &seconds_remaining in &self.seconds_remaining {
+                            for &timeouts in &self.timeouts {
+                                states.push(GridState {
+                                    down,
+                                    distance,
+                                    yardline,
+                                    score_diff,
+                                    seconds_remaining,
+                                    timeouts,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        states
+    }
+}
+
+impl GridState {
+    /// the offense is always the home team
+    pub fn to_game_state(&self) -> GameState {
+        let down = match self.down {
+            1 => Down::First,
+            2 => Down::Second,
+            3 => Down::Third,
+            4 => Down::Fourth,
+            _ => panic!("down must be in [1, 4], found {}", self.down),
+        };
+        assert!(
+            self.timeouts <= 3,
+            "timeouts must be in [0, 3], found {}",
+            self.timeouts
+        );
+        let to_go = match self.distance >= self.yardline {
+            true => ToGo::Goal,
+            false => ToGo::Yards(self.distance),
+        };
+        // 1800 seconds left is the start of the third, not the end of the second
+        let quarters_played = ((3600 - self.seconds_remaining.min(3600)) / 900).min(3);
+        let quarter = match quarters_played {
+            0 => Quarter::First,
+            1 => Quarter::Second,
+            2 => Quarter::Third,
+            _ => Quarter::Fourth,
+        };
+        let mut clock = GameClock::new();
+        clock.quarter = quarter;
+        clock.seconds_remaining = self.seconds_remaining - 900 * (3 - quarters_played);
+        GameState {
+            score: Score {
+                home: self.score_diff.max(0) as u8,
+                away: (-self.score_diff).max(0) as u8,
+            },
+            timeouts_used: TimeoutsUsed {
+                home: 3 - self.timeouts,
+                away: 3 - self.timeouts,
+            },
+            clock,
+            play: PlayState::Down(DownToGo {
+                possession: HomeAway::Home,
+                down,
+                to_go,
+                yards_to_goal: YardsToGoal::new(self.yardline),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StateValue {
+    pub state: GridState,
+    pub n_sims: u32,
+    // next score in the half, from the offense's side
+    pub prob_td: f32,
+    pub prob_fg: f32,
+    pub prob_safety: f32,
+    pub prob_no_score: f32,
+    pub prob_opp_safety: f32,
+    pub prob_opp_fg: f32,
+    pub prob_opp_td: f32,
+    pub expected_points: f32,
+    pub win_prob: f32,
+    pub mean_margin: f32,
+}
+
+fn half(quarter: Quarter) -> u8 {
+    match quarter {
+        Quarter::First | Quarter::Second => 1,
+        Quarter::Third | Quarter::Fourth => 2,
+        Quarter::Overtime | Quarter::EndOfGame => 3,
+    }
+}
+
+fn state_value(state: GridState, box_scores: &[BoxScore]) -> StateValue {
+    let n = box_scores.len() as f32;
+    let start_half = half(state.to_game_state().clock.quarter);
+    // td, fg, safety for the offense then the defense, then no score
+    let mut next_scores = [0.0; 7];
+    let (mut win_prob, mut mean_margin) = (0.0, 0.0);
+    for bs in box_scores {
+        let next_score = bs
+            .scoring_plays
+            .first()
+            .filter(|sp| half(sp.quarter) == start_half);
+        let ix = match next_score {
+            None => 6,
+            Some(sp) => {
+                let kind = match (sp.touchdown, sp.points) {
+                    (true, _) => 0,
+                    (false, 3) => 1,
+                    _ => 2,
+                };
+                match sp.team {
+                    HomeAway::Home => kind,
+                    HomeAway::Away => 3 + kind,
+                }
+            }
+        };
+        next_scores[ix] += 1.0 / n;
+
+        let margin = bs.score.home as f32 - bs.score.away as f32;
+        mean_margin += margin / n;
+        win_prob += match margin.partial_cmp(&0.0).unwrap() {
+            std::cmp::Ordering::Greater => 1.0,
+            std::cmp::Ordering::Equal => 0.5,
+            std::cmp::Ordering::Less => 0.0,
+        } / n;
+    }
+    let values = [
+        TD_VALUE,
+        FG_VALUE,
+        SAFETY_VALUE,
+        -TD_VALUE,
+        -FG_VALUE,
+        -SAFETY_VALUE,
+        0.0,
+    ];
+    StateValue {
+        state,
+        n_sims: box_scores.len() as u32,
+        prob_td: next_scores[0],
+        prob_fg: next_scores[1],
+        prob_safety: next_scores[2],
+        prob_opp_td: next_scores[3],
+        prob_opp_fg: next_scores[4],
+        prob_opp_safety: next_scores[5],
+        prob_no_score: next_scores[6],
+        expected_points: next_scores.iter().zip(values).map(|(p, v)| p * v).sum(),
+        win_prob,
+        mean_margin,
+    }
+}
+
+/// run the rest of the game `n_sims` times from every state in the grid. pass
+/// `game_params.neutral()` for league-average teams, or a real matchup with the
+/// home team on offense
+pub fn sim_state_values(
+    n_sims: u32,
+    game_params: &GameParamsDistribution,
+    grid: &StateGrid,
+) -> Vec<StateValue> {
+    let states = grid.states();
+    init_progress_bar(states.len());
+    enable_eta();
+    set_progress_bar_action("EP/WP", Color::Green, Style::Normal);
+    let start_time = std::time::Instant::now();
+
+    let values: Vec<StateValue> = states
+        .into_par_iter()
+        .map(|state| {
+            let box_scores: Vec<BoxScore> = (0..n_sims)
+                .map(|_| {
+                    // only matters for first half states
+                    let kicks_h2_start = HomeAway::home_if_true(random_bool(0.5));
+                    sim_game_from_state(game_params, false, state.to_game_state(), kicks_h2_start)
+                })
+                .collect();
+            inc_progress_bar();
+            state_value(state, &box_scores)
+        })
+        .collect();
+
+    finalize_progress_bar();
+    let (mins, secs) = mins_secs(std::time::Instant::now() - start_time);
+    log::info!(
+        "Simmed {} states {} times each in {:.0}m {:.0}s",
+        values.len(),
+        n_sims,
+        mins,
+        secs
+    );
+    values
+}
+
+pub fn write_state_values(slate_dir: &str, values: &[StateValue]) {
+    let proj_path = format!("{}/projections", slate_dir);
+    std::fs::create_dir_all(&proj_path).unwrap();
+    let mut wtr = csv::Writer::from_path(format!("{}/ep_wp.csv", proj_path)).unwrap();
+    wtr.write_record([
+        "down",
+        "distance",
+        "yardline_100",
+        "score_diff",
+        "seconds_remaining",
+        "timeouts",
+        "n_sims",
+        "prob_td",
+        "prob_fg",
+        "prob_safety",
+        "prob_no_score",
+        "prob_opp_safety",
+        "prob_opp_fg",
+        "prob_opp_td",
+        "ep",
+        "wp",
+        "mean_margin",
+    ])
+    .unwrap();
+    for value in values {
+        let state = &value.state;
+        wtr.write_record(&[
+            state.down.to_string(),
+            state.distance.to_string(),
+            state.yardline.to_string(),
+            state.score_diff.to_string(),
+            state.seconds_remaining.to_string(),
+            state.timeouts.to_string(),
+            value.n_sims.to_string(),
+            format!("{:.4}", value.prob_td),
+            format!("{:.4}", value.prob_fg),
+            format!("{:.4}", value.prob_safety),
+            format!("{:.4}", value.prob_no_score),
+            format!("{:.4}", value.prob_opp_safety),
+            format!("{:.4}", value.prob_opp_fg),
+            format!("{:.4}", value.prob_opp_td),
+            format!("{:.3}", value.expected_points),
+            format!("{:.4}", value.win_prob),
+            format!("{:.2}", value.mean_margin),
+        ])
+        .unwrap();
+    }
+    wtr.flush().unwrap();
+}
+
+#[derive(Debug, Deserialize)]
+struct StateValueLoader {
+    down: u8,
+    distance: u8,
+    yardline_100: u8,
+    score_diff: i8,
+    seconds_remaining: u16,
+    timeouts: u8,
+    ep: f32,
+    wp: f32,
+}
+
+// (down, distance, score diff, seconds remaining, timeouts)
+type CurveKey = (u8, u8, i8, u16, u8);
+
+/// EP and WP for any state, read off a simulated grid. yardline and score diff
+/// are interpolated, everything else snaps to the closest grid value
+#[derive(Debug, Clone)]
+pub struct StateValueTable {
+    downs: Vec<u8>,
+    distances: Vec<u8>,
+    score_diffs: Vec<i8>,
+    seconds_remaining: Vec<u16>,
+    timeouts: Vec<u8>,
+    // (yardline, ep, wp) sorted by yardline
+    curves: HashMap<CurveKey, Vec<(u8, f32, f32)>>,
+}
+
+fn sorted_unique<T: Copy + PartialOrd>(values: impl Iterator<Item = T>) -> Vec<T> {
+    let mut values: Vec<T> = values.collect();
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    values.dedup();
+    values
+}
+
+fn nearest<T: Copy + Into<f32>>(values: &[T], x: f32) -> T {
+    *values
+        .iter()
+        .min_by(|a, b| {
+            let (a, b): (f32, f32) = ((**a).into(), (**b).into());
+            (a - x).abs().partial_cmp(&(b - x).abs()).unwrap()
+        })
+        .unwrap()
+}
+
+/// the grid values either side of x, and how much weight the upper one gets
+fn bracket(values: &[i8], x: f32) -> (i8, i8, f32) {
+    let hi_ix = values.partition_point(|v| (*v as f32) < x);
+    match hi_ix {
+        0 => (values[0], values[0], 0.0),
+        ix if ix == values.len() => (values[ix - 1], values[ix - 1], 0.0),
+        ix => {
+            let (lo, hi) = (values[ix - 1], values[ix]);
+            (lo, hi, (x - lo as f32) / (hi as f32 - lo as f32))
+        }
+    }
+}
+
+fn interpolate(curve: &[(u8, f32, f32)], yardline: f32) -> (f32, f32) {
+    let hi_ix = curve.partition_point(|(yl, _, _)| (*yl as f32) < yardline);
+    match hi_ix {
+        0 => (curve[0].1, curve[0].2),
+        ix if ix == curve.len() => (curve[ix - 1].1, curve[ix - 1].2),
+        ix => {
+            let (lo, hi) = (curve[ix - 1], curve[ix]);
+            let w = (yardline - lo.0 as f32) / (hi.0 as f32 - lo.0 as f32);
+            (lo.1 + w * (hi.1 - lo.1), lo.2 + w * (hi.2 - lo.2))
+        }
+    }
+}
+
+impl StateValueTable {
+    pub fn new(values: &[StateValue]) -> StateValueTable {
+        let rows: Vec<(GridState, f32, f32)> = values
+            .iter()
+            .map(|v| (v.state, v.expected_points, v.win_prob))
+            .collect();
+        StateValueTable::from_rows(&rows)
+    }
+
+    /// read back a table written by `write_state_values`
+    pub fn load(path: &str) -> StateValueTable {
+        log::info!("loading EP/WP table from {}", path);
+        let mut reader =
+            Reader::from_path(path).unwrap_or_else(|_| panic!("Failed to load file at {}", path));
+        let rows: Vec<(GridState, f32, f32)> = reader
+            .deserialize()
+            .map(|row| {
+                let row: StateValueLoader = row.expect("failed to parse EP/WP row");
+                let state = GridState {
+                    down: row.down,
+                    distance: row.distance,
+                    yardline: row.yardline_100,
+                    score_diff: row.score_diff,
+                    seconds_remaining: row.seconds_remaining,
+                    timeouts: row.timeouts,
+                };
+                (state, row.ep, row.wp)
+            })
+            .collect();
+        StateValueTable::from_rows(&rows)
+    }
+
+    fn from_rows(rows: &[(GridState, f32, f32)]) -> StateValueTable {
+        let mut curves: HashMap<CurveKey, Vec<(u8, f32, f32)>> = HashMap::new();
+        for (state, ep, wp) in rows {
+            let key = (
+                state.down,
+                state.distance,
+                state.score_diff,
+                state.seconds_remaining,
+                state.timeouts,
+            );
+            curves
+                .entry(key)
+                .or_default()
+                .push((state.yardline, *ep, *wp));
+        }
+        for curve in curves.values_mut() {
+            curve.sort_by_key(|(yardline, _, _)| *yardline);
+        }
+        StateValueTable {
+            downs: sorted_unique(rows.iter().map(|r| r.0.down)),
+            distances: sorted_unique(rows.iter().map(|r| r.0.distance)),
+            score_diffs: sorted_unique(rows.iter().map(|r| r.0.score_diff)),
+            seconds_remaining: sorted_unique(rows.iter().map(|r| r.0.seconds_remaining)),
+            timeouts: sorted_unique(rows.iter().map(|r| r.0.timeouts)),
+            curves,
+        }
+    }
+
+    /// (expected points, win probability) for the team with the ball
+    pub fn lookup(&self, state: &GridState) -> (f32, f32) {
+        let down = nearest(&self.downs, state.down as f32);
+        let seconds_remaining = nearest(&self.seconds_remaining, state.seconds_remaining as f32);
+        let timeouts = nearest(&self.timeouts, state.timeouts as f32);
+        let mut distances = self.distances.clone();
+        distances.sort_by_key(|d| (*d as i16 - state.distance as i16).abs());
+
+        let (lo, hi, w) = bracket(&self.score_diffs, state.score_diff as f32);
+        let curve_value = |score_diff: i8| {
+            let curve = distances
+                .iter()
+                .find_map(|d| {
+                    self.curves
+                        .get(&(down, *d, score_diff, seconds_remaining, timeouts))
+                })
+                .unwrap_or_else(|| panic!("EP/WP table has no rows near {:?}", state));
+            interpolate(curve, state.yardline as f32)
+        };
+        let (lo_ep, lo_wp) = curve_value(lo);
+        let (hi_ep, hi_wp) = curve_value(hi);
+        (lo_ep + w * (hi_ep - lo_ep), lo_wp + w * (hi_wp - lo_wp))
+    }
+}
+
+/// EP/WP around one snap, from the offense's side
+#[derive(Debug, Clone)]
+pub struct PlayValue {
+    pub offense: HomeAway,
+    pub quarter: Quarter,
+    pub seconds_remaining: u16,
+    pub ep_before: f32,
+    pub ep_after: f32,
+    pub epa: f32,
+    pub wp_before: f32,
+    pub wp_after: f32,
+    pub wpa: f32,
+}
+
+fn game_seconds_remaining(clock: &GameClock) -> u16 {
+    let quarters_left = match clock.quarter {
+        Quarter::First => 3,
+        Quarter::Second => 2,
+        Quarter::Third => 1,
+        Quarter::Fourth | Quarter::Overtime | Quarter::EndOfGame => 0,
+    };
+    900 * quarters_left + clock.seconds_remaining
+}
+
+impl StateValueTable {
+    // `points` are added to the team's score first
+    fn lookup_team(&self, game_state: &GameState, dtg: &DownToGo, points: i16) -> (f32, f32) {
+        let team = dtg.possession;
+        let down = match dtg.down {
+            Down::First => 1,
+            Down::Second => 2,
+            Down::Third => 3,
+            Down::Fourth => 4,
+        };
+        let distance = match dtg.to_go {
+            ToGo::Yards(yards) => yards,
+            ToGo::Goal => dtg.yards_to_goal.0,
+        };
+        self.lookup(&GridState {
+            down,
+            distance,
+            yardline: dtg.yards_to_goal.0,
+            score_diff: (game_state.score.margin(team) + points).clamp(-127, 127) as i8,
+            seconds_remaining: game_seconds_remaining(&game_state.clock),
+            timeouts: game_state.timeouts_used.timeouts_remaining(team),
+        })
+    }
+
+    /// (expected points, win probability) for `team` in any game state. kicks
+    /// are valued as the receiving team's first and ten at their own 25
+    pub fn state_value(&self, game_state: &GameState, team: HomeAway) -> (f32, f32) {
+        if game_state.clock.quarter == Quarter::EndOfGame {
+            let wp = match game_state.score.margin(team) {
+                margin if margin > 0 => 1.0,
+                0 => 0.5,
+                _ => 0.0,
+            };
+            return (0.0, wp);
+        }
+        let (holder, (ep, wp)) = match game_state.play {
+            PlayState::Down(dtg) => (dtg.possession, self.lookup_team(game_state, &dtg, 0)),
+            PlayState::Kickoff(kicking) | PlayState::PuntAfterSafety(kicking) => {
+                let receiving = kicking.flip();
+                let dtg = DownToGo::first_and_ten(receiving, YardsToGoal::new(75));
+                (receiving, self.lookup_team(game_state, &dtg, 0))
+            }
+            PlayState::PointAfterTouchdown(scoring) => {
+                // assume the try is good, then kick off
+                let receiving = scoring.flip();
+                let dtg = DownToGo::first_and_ten(receiving, YardsToGoal::new(75));
+                (receiving, self.lookup_team(game_state, &dtg, -1))
+            }
+        };
+        match holder == team {
+            true => (ep, wp),
+            false => (-ep, 1.0 - wp),
+        }
+    }
+
+    /// value a snap from scrimmage given the states either side of it. a score
+    /// on the play is worth its next-score value, so a touchdown is worth 7
+    pub fn value_play(&self, before: &GameState, after: &GameState) -> Option<PlayValue> {
+        let offense = before.play.down_possession()?;
+        let (ep_before, wp_before) = self.state_value(before, offense);
+        let (ep_next, wp_after) = self.state_value(after, offense);
+        let net_points = after.score.margin(offense) - before.score.margin(offense);
+        let ep_after = match net_points {
+            0 if half(after.clock.quarter) != half(before.clock.quarter) => 0.0,
+            0 => ep_next,
+            6 => TD_VALUE,
+            -6 => -TD_VALUE,
+            points => points as f32,
+        };
+        Some(PlayValue {
+            offense,
+            quarter: before.clock.quarter,
+            seconds_remaining: before.clock.seconds_remaining,
+            ep_before,
+            ep_after,
+            epa: ep_after - ep_before,
+            wp_before,
+            wp_after,
+            wpa: wp_after - wp_before,
+        })
+    }
+}
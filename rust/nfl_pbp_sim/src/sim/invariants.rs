@@ -0,0 +1,202 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::{
+    sim::{play_result::PlayResult, GameSim},
+    start::HomeAway,
+    state::{
+        down::{PlayState, ToGo},
+        game_state::{GameState, Score},
+    },
+};
+
+// plays kept for the dump when an invariant breaks
+pub const RECENT_PLAYS: usize = 12;
+
+/// a game that panicked or broke an invariant
+#[derive(Debug, Clone)]
+pub struct SimFailure {
+    // reseed with this to replay the game that failed
+    pub seed: u64,
+    pub message: String,
+    // oldest first, ending with the play that failed. only kept when checked
+    pub recent_plays: Vec<String>,
+}
+
+impl std::fmt::Display for SimFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} (seed {})", self.message, self.seed)?;
+        for play in &self.recent_plays {
+            write!(f, "\n    {}", play)?;
+        }
+        Ok(())
+    }
+}
+
+/// validates the game after every play, when a sim is run in checked mode
+pub struct InvariantChecker {
+    pub seed: u64,
+    start_score: Score,
+    recent_plays: VecDeque<String>,
+}
+
+impl InvariantChecker {
+    pub fn new(seed: u64, game_state: &GameState) -> InvariantChecker {
+        InvariantChecker {
+            seed,
+            start_score: game_state.score.clone(),
+            recent_plays: VecDeque::with_capacity(RECENT_PLAYS),
+        }
+    }
+
+    /// log the state before a snap, so a panic mid-play still shows where it was
+    pub fn start_play(&mut self, before: &GameState) {
+        if self.recent_plays.len() == RECENT_PLAYS {
+            self.recent_plays.pop_front();
+        }
+        self.recent_plays.push_back(format!(
+            "{} ({}-{})",
+            before, before.score.home, before.score.away
+        ));
+    }
+
+    pub fn finish_play(&mut self, result: &PlayResult) {
+        if let Some(play) = self.recent_plays.back_mut() {
+            *play = format!("{} => {}", play, result);
+        }
+    }
+
+    pub fn failure(&self, message: String) -> SimFailure {
+        SimFailure {
+            seed: self.seed,
+            message,
+            recent_plays: self.recent_plays.iter().cloned().collect(),
+        }
+    }
+
+    /// every invariant of the transition from `before` to the sim's current state
+    pub fn check(&self, before: &GameState, sim: &GameSim) -> Result<(), String> {
+        self.check_score(sim)?;
+        check_play_state(&sim.game_state.play)?;
+        check_timeouts(&sim.game_state)?;
+        check_clock(before, &sim.game_state)?;
+        for team in [HomeAway::Home, HomeAway::Away] {
+            check_team_stats(sim, team)?;
+        }
+        Ok(())
+    }
+
+    fn check_score(&self, sim: &GameSim) -> Result<(), String> {
+        let box_score = &sim.box_score;
+        for team in [HomeAway::Home, HomeAway::Away] {
+            let scored: u32 = box_score
+                .scoring_plays
+                .iter()
+                .filter(|p| p.team == team)
+                .map(|p| p.points as u32)
+                .sum();
+            let expected = self.start_score.team_points(team) as u32 + scored;
+            let points = box_score.score.team_points(team) as u32;
+            if points != expected {
+                return Err(format!(
+                    "{} score is {} but its scoring plays add up to {}",
+                    team, points, expected
+                ));
+            }
+            if sim.game_state.score.team_points(team) as u32 != points {
+                return Err(format!(
+                    "{} game state score {} differs from box score {}",
+                    team,
+                    sim.game_state.score.team_points(team),
+                    points
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_play_state(play: &PlayState) -> Result<(), String> {
+    if let PlayState::Down(dtg) = play {
+        let ytg = dtg.yards_to_goal.0;
+        if !(1..=99).contains(&ytg) {
+            return Err(format!("yardline {} is outside [1, 99] on {}", ytg, dtg));
+        }
+        if let ToGo::Yards(yards) = dtg.to_go {
+            if yards == 0 || yards > ytg {
+                return Err(format!("{} to go is inconsistent with {}", yards, dtg));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_timeouts(game_state: &GameState) -> Result<(), String> {
+    let used = &game_state.timeouts_used;
+    match used.home > 3 || used.away > 3 {
+        true => Err(format!(
+            "timeouts used out of bounds, home {} away {}",
+            used.home, used.away
+        )),
+        false => Ok(()),
+    }
+}
+
+fn check_clock(before: &GameState, after: &GameState) -> Result<(), String> {
+    let (prev, next) = (&before.clock, &after.clock);
+    if next.quarter < prev.quarter {
+        return Err(format!("clock went back from {} to {}", prev, next));
+    }
+    if next.quarter == prev.quarter && next.seconds_remaining > prev.seconds_remaining {
+        return Err(format!("clock went back from {} to {}", prev, next));
+    }
+    Ok(())
+}
+
+/// player box scores must add up to the team's play counters
+fn check_team_stats(sim: &GameSim, team: HomeAway) -> Result<(), String> {
+    let params = sim.game_params.get_team(team);
+    let player_ids: HashSet<&String> = params
+        .skill_players
+        .keys()
+        .chain(params.qbs.iter().map(|qb| &qb.player_id))
+        .collect();
+    let plays = match team {
+        HomeAway::Home => &sim.box_score.plays.home,
+        HomeAway::Away => &sim.box_score.plays.away,
+    };
+
+    let (mut targets, mut carries) = (0u32, 0u32);
+    for (player_id, box_score) in &sim.box_score.skill_players {
+        if player_ids.contains(player_id) {
+            targets += box_score.targets as u32;
+            carries += box_score.carries as u32;
+        }
+    }
+    let (mut attempts, mut scrambles) = (0u32, 0u32);
+    for (player_id, box_score) in &sim.box_score.passers {
+        if player_ids.contains(player_id) {
+            attempts += box_score.attempts as u32;
+            scrambles += box_score.scrambles as u32;
+        }
+    }
+
+    if targets != plays.targets as u32 {
+        return Err(format!(
+            "{} players have {} targets but the team has {}",
+            team, targets, plays.targets
+        ));
+    }
+    if carries != plays.run as u32 + scrambles {
+        return Err(format!(
+            "{} players have {} carries but the team has {} runs and {} scrambles",
+            team, carries, plays.run, scrambles
+        ));
+    }
+    if attempts > plays.dropbacks as u32 {
+        return Err(format!(
+            "{} passers have {} attempts on {} dropbacks",
+            team, attempts, plays.dropbacks
+        ));
+    }
+    Ok(())
+}
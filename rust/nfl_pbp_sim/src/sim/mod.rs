@@ -1,8 +1,15 @@
 pub mod box_score;
+pub mod decision;
+pub mod expected_points;
+pub mod invariants;
 pub mod play_result;
 pub mod run;
 
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+
 use crate::{
+    box_score::drive::DriveResult,
     game_loop::{
         field_goals::FG_SNAP_DISTANCE,
         play_calling::{choose_playcall, PlayType},
@@ -30,10 +37,13 @@ use crate::{
         game_state::GameState,
         yards_to_goal::YardsToGoal,
     },
-    util::stats::random_bool,
+    util::stats::{new_seed, random_bool, seed_rng},
 };
 
 use crate::sim::box_score::BoxScore;
+use crate::sim::decision::Decision;
+use crate::sim::expected_points::StateValueTable;
+use crate::sim::invariants::{InvariantChecker, SimFailure};
 use crate::sim::play_result::{
     DropbackOutcome, FieldGoalResult, KickingResult, PlayResult, RunResult, RushingOutcome,
     SackOutcome, TurnoverOutcome,
@@ -54,6 +64,10 @@ pub struct GameSim {
     pub game_state: GameState,
     pub box_score: BoxScore,
     pub game_params: GameParams,
+    // when set, every snap is valued and its EPA credited in the box score
+    pub state_values: Option<Arc<StateValueTable>>,
+    // overrides the playcall or two-point models on the next snap only
+    pub forced_decision: Option<Decision>,
 }
 
 impl std::fmt::Display for GameSim {
@@ -88,6 +102,31 @@ impl GameSim {
             game_params,
             game_state,
             box_score,
+            state_values: None,
+            forced_decision: None,
+        }
+    }
+
+    pub fn with_forced_decision(mut self, decision: Decision) -> GameSim {
+        self.forced_decision = Some(decision);
+        self
+    }
+
+    pub fn with_state_values(mut self, state_values: Arc<StateValueTable>) -> GameSim {
+        self.state_values = Some(state_values);
+        self
+    }
+
+    fn apply_play_value(&mut self, play: &PlayResult, before: &GameState) {
+        if let PlayResult::Timeout(_) = play {
+            return;
+        }
+        let value = match &self.state_values {
+            Some(state_values) => state_values.value_play(before, &self.game_state),
+            None => None,
+        };
+        if let Some(value) = value {
+            self.box_score.apply_play_value(play, value);
         }
     }
 
@@ -129,9 +168,12 @@ impl GameSim {
     pub fn next_play(&mut self) -> (PlayResult, u16, ClockStatus, bool) {
         log::debug!("{}", self);
         self.register_down();
+        self.box_score
+            .start_drive(&self.game_state.play, &self.game_state.clock);
         let play_call = choose_playcall(&self);
         let result: PlayResult = self.play_result(&play_call);
         log::debug!("{}\n", result);
+        self.forced_decision = None;
 
         if self.game_params.sim_injuries {
             let injuries = Injury::sim_injuries(&result, self.offense_params());
@@ -141,10 +183,22 @@ impl GameSim {
         }
 
         // add stats to the box score
-        self.box_score.apply_stats(&result, &self.game_state.play);
+        let possession = self.game_state.play.possession();
+        let points_before = self.box_score.score.team_points(possession);
+        self.box_score.apply_stats(
+            &result,
+            &self.game_state.play,
+            self.game_state.clock.quarter,
+        );
         self.game_state.score.update(&self.box_score.score);
 
         let (state, mut clock_stops) = GameSim::next_play_state(&result, &self.game_state.play);
+        self.box_score.apply_drive(
+            &result,
+            &self.game_state.play,
+            &state,
+            self.box_score.score.team_points(possession) - points_before,
+        );
         self.game_state.update_play(state);
 
         let mut duration = 0;
@@ -384,7 +438,13 @@ impl GameSim {
     /// this is called when there is no INT/FumRec.
     /// here is where we handle turnover on downs
     fn advance_down(down_to_go: DownToGo, yards_gained: i8) -> (PlayState, ClockStatus) {
-        let (next_down, change_of_possession) = down_to_go.down.next();
+        let (next_down, out_of_downs) = down_to_go.down.next();
+        let moves_the_chains = match down_to_go.to_go {
+            ToGo::Yards(to_go) => yards_gained >= to_go as i8,
+            ToGo::Goal => false,
+        };
+        // a fourth down conversion keeps the ball
+        let change_of_possession = out_of_downs && !moves_the_chains;
 
         let offense_ends_at = down_to_go.yards_to_goal - yards_gained;
         let next_down_to_go = match change_of_possession {
@@ -575,7 +635,8 @@ impl GameSim {
             KickingResult::ReturnedForTouchdown => {
                 PlayState::PointAfterTouchdown(prev_state.returning_team())
             }
-            KickingResult::ReturnedForYards(yards_to_goal) => PlayState::Down(
+            KickingResult::ReturnedForYards(yards_to_goal)
+            | KickingResult::OnsideNotRecovered(yards_to_goal) => PlayState::Down(
                 DownToGo::first_and_ten(prev_state.returning_team(), *yards_to_goal),
             ),
             KickingResult::OnsideRecovery(yards_to_goal) => PlayState::Down(
@@ -612,20 +673,121 @@ impl GameSim {
     }
 }
 
-pub fn sim_game(game_params: &GameParamsDistribution, sim_injuries: bool) -> BoxScore {
-    let game_start = GameStart::new();
-    let kicks_h2_start = game_start.received_h1.clone();
-    let game_state = GameState::new(game_start);
-    let mut sim = GameSim::new(
+/// sim a game from kickoff on a fresh seed. a panic comes back as a failure
+/// with the seed, so the game can be replayed with `sim_game_checked`
+pub fn sim_game(
+    game_params: &GameParamsDistribution,
+    sim_injuries: bool,
+) -> Result<BoxScore, SimFailure> {
+    sim_from_kickoff(game_params, sim_injuries, new_seed(), None, false)
+}
+
+/// play out the rest of a game from any state. the box score only has stats
+/// from here on, but starts from the state's score
+pub fn sim_game_from_state(
+    game_params: &GameParamsDistribution,
+    sim_injuries: bool,
+    game_state: GameState,
+    kicks_h2_start: HomeAway,
+) -> BoxScore {
+    let sim = GameSim::new(
         game_params.to_game_params().injuries(sim_injuries),
         game_state,
     );
+    play_out(sim, kicks_h2_start)
+}
+
+/// sim a game from kickoff, valuing every snap with the EP/WP table
+pub fn sim_game_with_values(
+    game_params: &GameParamsDistribution,
+    sim_injuries: bool,
+    state_values: &Arc<StateValueTable>,
+) -> Result<BoxScore, SimFailure> {
+    sim_from_kickoff(
+        game_params,
+        sim_injuries,
+        new_seed(),
+        Some(state_values),
+        false,
+    )
+}
+
+/// sim a game from kickoff with every play validated. a broken invariant comes
+/// back as a failure, along with the last few plays
+pub fn sim_game_checked(
+    game_params: &GameParamsDistribution,
+    sim_injuries: bool,
+    seed: u64,
+) -> Result<BoxScore, SimFailure> {
+    sim_from_kickoff(game_params, sim_injuries, seed, None, true)
+}
+
+fn sim_from_kickoff(
+    game_params: &GameParamsDistribution,
+    sim_injuries: bool,
+    seed: u64,
+    state_values: Option<&Arc<StateValueTable>>,
+    check: bool,
+) -> Result<BoxScore, SimFailure> {
+    seed_rng(seed);
+    let game_start = GameStart::new();
+    let kicks_h2_start = game_start.received_h1;
+    let game_state = GameState::new(game_start);
+    let mut checker = InvariantChecker::new(seed, &game_state);
+    let outcome = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut sim = GameSim::new(
+            game_params.to_game_params().injuries(sim_injuries),
+            game_state,
+        );
+        if let Some(state_values) = state_values {
+            sim = sim.with_state_values(state_values.clone());
+        }
+        run_plays(sim, kicks_h2_start, check.then_some(&mut checker))
+    }));
+    match outcome {
+        Ok(Ok(box_score)) => Ok(box_score),
+        Ok(Err(message)) => Err(checker.failure(message)),
+        Err(payload) => {
+            let message = match payload.downcast_ref::<&str>() {
+                Some(message) => message.to_string(),
+                None => match payload.downcast_ref::<String>() {
+                    Some(message) => message.clone(),
+                    None => "unknown panic".into(),
+                },
+            };
+            Err(checker.failure(format!("panicked: {}", message)))
+        }
+    }
+}
+
+fn play_out(sim: GameSim, kicks_h2_start: HomeAway) -> BoxScore {
+    run_plays(sim, kicks_h2_start, None).expect("unchecked sims cannot break an invariant")
+}
+
+fn run_plays(
+    mut sim: GameSim,
+    kicks_h2_start: HomeAway,
+    mut checker: Option<&mut InvariantChecker>,
+) -> Result<BoxScore, String> {
+    sim.box_score.score.update(&sim.game_state.score);
     // log::info!("\n\nbeginning of game");
     // let (mut last_play, mut last_result) = (
     //     sim.game_state.play.clone(),
     //     PlayResult::Timeout(HomeAway::Away),
     // );
     while !sim.end_of_game() {
+        let (quarter, seconds_remaining) = (
+            sim.game_state.clock.quarter,
+            sim.game_state.clock.seconds_remaining,
+        );
+        let on_drive = sim.game_state.play.down_possession().is_some();
+        let before = match sim.state_values.is_some() || checker.is_some() {
+            true => Some(sim.game_state.clone()),
+            false => None,
+        };
+        if let (Some(checker), Some(before)) = (checker.as_deref_mut(), &before) {
+            checker.start_play(before);
+        }
         let (result, play_duration, clock_stops, rtk_end_play) = sim.next_play();
 
         let is_timeout = match sim.check_timeout() {
@@ -661,6 +823,21 @@ pub fn sim_game(game_params: &GameParamsDistribution, sim_injuries: bool) -> Box
                 true => false,
             },
         };
+        if on_drive {
+            sim.box_score.add_drive_seconds(
+                sim.game_state
+                    .clock
+                    .seconds_since(quarter, seconds_remaining),
+            );
+        }
+        if sim.game_state.clock.quarter != quarter {
+            match sim.game_state.clock.quarter {
+                Quarter::Third | Quarter::Overtime | Quarter::EndOfGame => {
+                    sim.box_score.end_drive(DriveResult::EndOfHalf)
+                }
+                _ => {}
+            }
+        }
         if rtk_end_play | rtk_run_clock {
             let kicking_team = match sim.game_state.clock.quarter {
                 Quarter::Third => kicks_h2_start,
@@ -672,6 +849,13 @@ pub fn sim_game(game_params: &GameParamsDistribution, sim_injuries: bool) -> Box
             };
             sim.game_state.play = PlayState::Kickoff(kicking_team);
         }
+        if let Some(before) = before {
+            sim.apply_play_value(&result, &before);
+            if let Some(checker) = checker.as_deref_mut() {
+                checker.finish_play(&result);
+                checker.check(&before, &sim)?;
+            }
+        }
         // if sim.game_state.play.safe_yards_for_touchdown() == 0 {
         //     log::info!("{} - {}", last_play, last_result);
         // }
@@ -683,5 +867,5 @@ pub fn sim_game(game_params: &GameParamsDistribution, sim_injuries: bool) -> Box
         sim.box_score.score.home,
         sim.box_score.score.away
     );
-    sim.box_score
+    Ok(sim.box_score)
 }
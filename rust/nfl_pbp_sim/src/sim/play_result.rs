@@ -413,6 +413,8 @@ pub enum KickingResult {
     FumbleLost(TurnoverOutcome),
     Blocked(TurnoverOutcome),
     OnsideRecovery(YardsToGoal),
+    // receiving team fell on the onside kick
+    OnsideNotRecovered(YardsToGoal),
 }
 
 impl std::fmt::Display for KickingResult {
@@ -429,6 +431,9 @@ impl std::fmt::Display for KickingResult {
             KickingResult::OnsideRecovery(yards_to_goal) => {
                 write!(f, "Onside Kick Recovered at {}", yards_to_goal)
             }
+            KickingResult::OnsideNotRecovered(yards_to_goal) => {
+                write!(f, "Onside Kick Not Recovered at {}", yards_to_goal)
+            }
         }
     }
 }
@@ -681,6 +686,28 @@ impl PlayResult {
         }
     }
 
+    /// offense lost the ball on an interception or fumble
+    pub fn is_turnover(&self) -> bool {
+        let run_turnover =
+            |outcome: &RushingOutcome| matches!(outcome, RushingOutcome::FumbleLost(_, _));
+        match self {
+            PlayResult::Dropback(result) => match &result.outcome {
+                DropbackOutcome::Target(target_result) => matches!(
+                    target_result.outcome,
+                    TargetOutcome::CatchThenFumble(_, _) | TargetOutcome::Interception(_, _)
+                ),
+                DropbackOutcome::Sack(sack_outcome) => {
+                    matches!(sack_outcome, SackOutcome::FumbleLost(_))
+                }
+                DropbackOutcome::QbScramble(run_result) => run_turnover(&run_result.outcome),
+                _ => false,
+            },
+            PlayResult::DesignedRun(result) => run_turnover(&result.outcome),
+            PlayResult::QbKneel(result) => run_turnover(&result.outcome),
+            _ => false,
+        }
+    }
+
     pub fn is_ot_ender(&self) -> bool {
         match self {
             PlayResult::Dropback(result) => match &result.outcome {
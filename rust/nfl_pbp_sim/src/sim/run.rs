@@ -1,10 +1,15 @@
+use std::sync::Arc;
+
 use progress_bar::*;
 use rayon::prelude::*;
 
 use crate::{
     params::GameParamsDistribution,
-    sim::{box_score::BoxScore, sim_game},
-    util::clock::mins_secs,
+    sim::{
+        box_score::BoxScore, expected_points::StateValueTable, invariants::SimFailure, sim_game,
+        sim_game_checked, sim_game_with_values,
+    },
+    util::{clock::mins_secs, stats::new_seed},
 };
 
 pub fn sim_box_scores_rayon(
@@ -12,26 +17,147 @@ pub fn sim_box_scores_rayon(
     game_params: &Vec<GameParamsDistribution>,
     sim_injuries: bool,
     progress_name: &str,
+) -> Vec<Vec<BoxScore>> {
+    sim_box_scores_with(n, game_params, progress_name, 0, |gp| {
+        sim_game(gp, sim_injuries)
+    })
+}
+
+/// same as `sim_box_scores_rayon`, but with every snap valued for EPA/WPA
+pub fn sim_box_scores_with_values(
+    n: u32,
+    game_params: &[GameParamsDistribution],
+    sim_injuries: bool,
+    state_values: &Arc<StateValueTable>,
+    progress_name: &str,
+) -> Vec<Vec<BoxScore>> {
+    sim_box_scores_with(n, game_params, progress_name, 0, |gp| {
+        sim_game_with_values(gp, sim_injuries, state_values)
+    })
+}
+
+/// same as `sim_box_scores_rayon`, but every play is checked against the
+/// game's invariants. a failed game is retried on up to `max_retries` new seeds
+/// before its sim is dropped. retries bias the sims toward games that finish,
+/// so leave it at 0 unless failures are rare
+pub fn sim_box_scores_checked(
+    n: u32,
+    game_params: &[GameParamsDistribution],
+    sim_injuries: bool,
+    max_retries: u8,
+    progress_name: &str,
+) -> Vec<Vec<BoxScore>> {
+    sim_box_scores_with(n, game_params, progress_name, max_retries, |gp| {
+        sim_game_checked(gp, sim_injuries, new_seed())
+    })
+}
+
+/// the game's box score, unless it failed on every try, and how many tries failed
+fn sim_with_retries(
+    gp: &GameParamsDistribution,
+    max_retries: u8,
+    sim: &(impl Fn(&GameParamsDistribution) -> Result<BoxScore, SimFailure> + Sync),
+) -> (Option<BoxScore>, u8) {
+    let mut failures = 0;
+    while failures <= max_retries {
+        match sim(gp) {
+            Ok(box_score) => return (Some(box_score), failures),
+            Err(failure) => {
+                failures += 1;
+                log::error!(
+                    "{} @ {} failed: {}",
+                    gp.away.team.team,
+                    gp.home.team.team,
+                    failure
+                );
+            }
+        }
+    }
+    (None, failures)
+}
+
+/// a sim is dropped if any of its games fails on every try, so every kept sim
+/// still has every game
+fn sim_box_scores_with(
+    n: u32,
+    game_params: &[GameParamsDistribution],
+    progress_name: &str,
+    max_retries: u8,
+    sim: impl Fn(&GameParamsDistribution) -> Result<BoxScore, SimFailure> + Sync,
 ) -> Vec<Vec<BoxScore>> {
     init_progress_bar(n as usize);
     enable_eta();
     set_progress_bar_action(progress_name, Color::Green, Style::Normal);
     let start_time = std::time::Instant::now();
 
-    let box_scores = (0..n)
+    let results = (0..n)
         .into_par_iter()
         .map(|_| {
-            let gp = game_params
-                .iter()
-                .map(|gp| sim_game(gp, sim_injuries))
-                .collect::<Vec<BoxScore>>();
+            let mut box_scores = Some(vec![]);
+            let mut failures = vec![0; game_params.len()];
+            for (ix, gp) in game_params.iter().enumerate() {
+                let (box_score, n_failed) = sim_with_retries(gp, max_retries, &sim);
+                failures[ix] = n_failed;
+                box_scores = match (box_scores, box_score) {
+                    (Some(mut box_scores), Some(box_score)) => {
+                        box_scores.push(box_score);
+                        Some(box_scores)
+                    }
+                    _ => None,
+                };
+            }
             inc_progress_bar();
-            gp
+            (box_scores, failures)
         })
-        .collect::<Vec<Vec<BoxScore>>>();
+        .collect::<Vec<(Option<Vec<BoxScore>>, Vec<u8>)>>();
 
     finalize_progress_bar();
 
+    // (games that never finished, games that finished on a retry)
+    let mut game_failures = vec![(0, 0); game_params.len()];
+    let mut box_scores = vec![];
+    let mut n_retried_sims = 0;
+    for (sim_box_scores, failures) in results {
+        for (ix, n_failed) in failures.iter().enumerate() {
+            match *n_failed > max_retries {
+                true => game_failures[ix].0 += 1,
+                false if *n_failed > 0 => game_failures[ix].1 += 1,
+                false => {}
+            }
+        }
+        if let Some(sim_box_scores) = sim_box_scores {
+            if failures.iter().any(|n_failed| *n_failed > 0) {
+                n_retried_sims += 1;
+            }
+            box_scores.push(sim_box_scores);
+        }
+    }
+    for (gp, (n_failed, n_retried)) in game_params.iter().zip(game_failures) {
+        if n_failed + n_retried > 0 {
+            log::warn!(
+                "{} @ {}: {} failed games, {} finished on a retry",
+                gp.away.team.team,
+                gp.home.team.team,
+                n_failed,
+                n_retried
+            );
+        }
+    }
+    if box_scores.len() < n as usize {
+        log::warn!(
+            "dropped {} of {} sims with a failed game",
+            n as usize - box_scores.len(),
+            n
+        );
+    }
+    if n_retried_sims > 0 {
+        log::warn!(
+            "kept {} of {} sims with a game that only finished on a retry",
+            n_retried_sims,
+            n
+        );
+    }
+
     let end_sim_time = std::time::Instant::now();
     let (mins, secs) = mins_secs(end_sim_time - start_time);
     log::info!(
@@ -52,7 +178,7 @@ pub fn sim_many(
     for _ in 0..n_sims {
         let mut box_scores = vec![];
         for gp in game_params {
-            box_scores.push(sim_game(gp, sim_injuries));
+            box_scores.push(sim_game(gp, sim_injuries).unwrap_or_else(|f| panic!("{}", f)));
         }
         all_box_scores.push(box_scores)
     }
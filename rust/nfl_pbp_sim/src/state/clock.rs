@@ -1,4 +1,4 @@
-#[derive(Debug, PartialEq, Hash, Clone, Copy, serde::Serialize)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, serde::Serialize)]
 pub enum Quarter {
     First,
     Second,
@@ -37,7 +37,7 @@ impl Quarter {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GameClock {
     pub quarter: Quarter,
     pub seconds_remaining: u16,
@@ -129,6 +129,14 @@ impl GameClock {
         }
     }
 
+    /// seconds run off since the clock read `seconds_remaining` in `quarter`
+    pub fn seconds_since(&self, quarter: Quarter, seconds_remaining: u16) -> u16 {
+        match self.quarter == quarter {
+            true => seconds_remaining.saturating_sub(self.seconds_remaining),
+            false => seconds_remaining,
+        }
+    }
+
     pub fn game_minutes_remaining(&self) -> f32 {
         let this_quarter_seconds_remaining = (self.seconds_remaining + 1) as f32;
         let quarters_remaining = match self.quarter {
@@ -198,6 +198,8 @@ impl PlayState {
     pub fn safe_yards_for_first(&self) -> u8 {
         match self {
             PlayState::Down(down_to_go) => down_to_go.yards_for_first(),
+            // same 4th and goal from the 2 as `expect_downtogo`
+            PlayState::PointAfterTouchdown(_) => self.expect_downtogo().yards_for_first(),
             _ => 0,
         }
     }
@@ -205,6 +207,7 @@ impl PlayState {
     pub fn safe_yards_for_touchdown(&self) -> u8 {
         match self {
             PlayState::Down(down_to_go) => down_to_go.yards_to_goal.0,
+            PlayState::PointAfterTouchdown(_) => self.expect_downtogo().yards_to_goal.0,
             _ => 50,
         }
     }
@@ -215,6 +218,7 @@ impl PlayState {
                 ToGo::Goal => true,
                 ToGo::Yards(_) => false,
             },
+            PlayState::PointAfterTouchdown(_) => true,
             _ => false,
         }
     }
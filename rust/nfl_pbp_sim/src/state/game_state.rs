@@ -18,6 +18,18 @@ impl Score {
         Score { home: 0, away: 0 }
     }
 
+    pub fn team_points(&self, team: HomeAway) -> u8 {
+        match team {
+            HomeAway::Home => self.home,
+            HomeAway::Away => self.away,
+        }
+    }
+
+    /// points the team leads by, negative when it trails
+    pub fn margin(&self, team: HomeAway) -> i16 {
+        self.team_points(team) as i16 - self.team_points(team.flip()) as i16
+    }
+
     fn is_game_tied(&self) -> bool {
         self.home == self.away
     }
@@ -120,7 +132,7 @@ impl Plays {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TimeoutsUsed {
     pub home: u8,
     pub away: u8,
@@ -154,7 +166,7 @@ impl TimeoutsUsed {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GameState {
     pub score: Score,
     pub timeouts_used: TimeoutsUsed,
@@ -2,13 +2,35 @@ extern crate log;
 extern crate rand;
 extern crate statrs;
 
+use std::cell::RefCell;
+
 use rand::distributions::{Distribution, WeightedIndex};
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use rand_distr::{Poisson, WeightedError};
 use statrs::distribution::{
-    Beta, ContinuousCDF, Discrete, NegativeBinomial, Normal, Poisson as PoissonDist,
+    Beta, ContinuousCDF, Discrete, Gamma, NegativeBinomial, Normal, Poisson as PoissonDist,
 };
 
+thread_local! {
+    // every draw in the sim comes from here, so a seeded game replays exactly
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+fn with_rng<T>(f: impl FnOnce(&mut StdRng) -> T) -> T {
+    RNG.with(|rng| f(&mut rng.borrow_mut()))
+}
+
+/// reseed this thread's generator, e.g. to replay a failed game
+pub fn seed_rng(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// a fresh seed from entropy, independent of the sim's generator
+pub fn new_seed() -> u64 {
+    rand::random()
+}
+
 pub fn random_discrete<T: Clone + std::fmt::Debug>(
     weighted_choices: Vec<(T, f32)>,
 ) -> Result<T, WeightedError> {
@@ -16,8 +38,7 @@ pub fn random_discrete<T: Clone + std::fmt::Debug>(
         WeightedIndex::new(weighted_choices.iter().map(|(_, w)| *w))?;
 
     let choices: Vec<&T> = weighted_choices.iter().map(|(s, _)| s).collect();
-    let mut rng = rand::thread_rng();
-    let choice = weighted_index.sample(&mut rng);
+    let choice = with_rng(|rng| weighted_index.sample(rng));
     Ok(choices[choice].clone())
 }
 
@@ -25,8 +46,7 @@ pub fn random_bool(prob_true: f32) -> bool {
     if prob_true < 0.0 || prob_true > 1.0 {
         panic!("Probability must be between 0.0 and 1.0");
     }
-    let mut rng = rand::thread_rng();
-    rng.gen::<f32>() < prob_true
+    with_rng(|rng| rng.gen::<f32>()) < prob_true
 }
 
 pub fn sigmoid_prob(z: f32) -> f32 {
@@ -47,10 +67,14 @@ pub fn normal_cdf(mean: f32, stdev: f32, x: f32) -> f32 {
     normal.cdf(x as f64) as f32
 }
 
+pub fn random_normal(mean: f32, stdev: f32) -> f32 {
+    let normal = Normal::new(mean as f64, stdev as f64).unwrap();
+    with_rng(|rng| normal.sample(rng)) as f32
+}
+
 pub fn random_poisson(lambda: f32) -> f32 {
     let poisson = Poisson::new(lambda).unwrap();
-    let mut rng = rand::thread_rng();
-    poisson.sample(&mut rng)
+    with_rng(|rng| poisson.sample(rng))
 }
 
 pub fn double_truncated_poisson(lambda: f32, min_inclusive: u8, max_exclusive: u8) -> u8 {
@@ -109,8 +133,7 @@ fn get_negbinom(mean: f32, var: f32) -> NegativeBinomial {
 
 pub fn random_negbinom(mean: f32, var: f32) -> u8 {
     let neg_binom = get_negbinom(mean, var);
-    let mut rng = rand::thread_rng();
-    let sample = neg_binom.sample(&mut rng);
+    let sample = with_rng(|rng| neg_binom.sample(rng));
 
     // maybe a little unsafe but we will check for this downstream;
     // only used for yards, which will be under 100 always
@@ -139,7 +162,20 @@ pub fn double_truncated_negbinom(mean: f32, var: f32, min_inclusive: u8, max_exc
 
 pub fn sample_beta(shape_a: f32, shape_b: f32) -> f32 {
     let distr = Beta::new(shape_a as f64, shape_b as f64).unwrap();
-    distr.sample(&mut rand::thread_rng()) as f32
+    with_rng(|rng| distr.sample(rng)) as f32
+}
+
+/// shares that sum to one, each with mean alpha / sum(alphas)
+pub fn sample_dirichlet(alphas: &[f32]) -> Vec<f32> {
+    let draws: Vec<f64> = alphas
+        .iter()
+        .map(|alpha| {
+            let distr = Gamma::new(*alpha as f64, 1.0).unwrap();
+            with_rng(|rng| distr.sample(rng))
+        })
+        .collect();
+    let total: f64 = draws.iter().sum();
+    draws.iter().map(|d| (d / total) as f32).collect()
 }
 
 pub fn get_linreg_slope_intercept(data: Vec<(f64, f64)>) -> (f64, f64) {
@@ -4,38 +4,186 @@ extern crate nfl_pbp_sim;
 use std::env;
 
 use nfl_pbp_sim::{
-    params::{burn_in::burn_in_params, GameParams},
-    projections::{math::accumulate_projections, writer::ProjectionsWriter},
-    sim::run::sim_box_scores_rayon,
+    backtest::samples::{sample_box_scores, write_samples},
+    diagnostics::{check_rates, vs_average_opponent, write_diagnostics, Baseline},
+    markets::{
+        game_lines::write_game_lines,
+        periods::write_period_lines,
+        props::{write_prop_ladders, PropLadderConfig},
+        touchdowns::write_touchdown_scorers,
+    },
+    params::{
+        burn_in::{cached_burn_in_params, BurnInConfig},
+        calibration::{calibrate_to_market, CalibrationConfig},
+        prop_calibration::{
+            calibrate_props, write_prop_calibration_report, PropCalibrationConfig, PropLoader,
+        },
+        skill_player::ShareSampling,
+        slate_builder::build_slate_params,
+        slate_file::SlateFile,
+        GameParams, GameParamsDistribution,
+    },
+    projections::{
+        drives::write_drive_summary,
+        json_writer::{write_json_projections, RunMetadata},
+        math::accumulate_projections,
+        writer::ProjectionsWriter,
+    },
+    sim::run::{sim_box_scores_checked, sim_box_scores_rayon},
 };
 
 const SLATE_ID: &str = "2023-12-11";
 const N_SIMS: u32 = 50_000;
 const SIM_INJURIES: bool = true;
+// `Joint` draws each team's target and carry shares from one dirichlet so teammates
+// trade off usage, `Independent` draws every player's share on its own
+const SHARE_SAMPLING: ShareSampling = ShareSampling::Independent;
+// validate every play and drop sims that break the game's invariants
+const CHECK_INVARIANTS: bool = false;
+// new seeds to try a failed game on before its sim is dropped. retried games
+// bias the sims toward ones that finish
+const MAX_RETRIES: u8 = 0;
+const TD_SCORER_VIG: f32 = 0.0;
+// sims kept per player and stat for backtesting
+const N_SAMPLES: usize = 2_000;
+const N_DIAGNOSTIC_SIMS: u32 = 2_000;
 
+// `nfl_sim_example diagnostics [average]` checks league rates instead of projecting,
+// and `nfl_sim_example build` writes the slate's params from its schedule
 fn main() {
-    let slates_dir = get_slates_dir();
-    run_slate(format!("{}/{}", slates_dir, SLATE_ID));
+    env_logger::init();
+    let slate_dir = format!("{}/{}", get_slates_dir(), SLATE_ID);
+    let args: Vec<String> = env::args().collect();
+    match args.get(1).map(|a| a.as_str()) {
+        Some("diagnostics") => {
+            run_diagnostics(slate_dir, args.get(2).is_some_and(|a| a == "average"))
+        }
+        Some("build") => build_params(slate_dir),
+        _ => run_slate(slate_dir),
+    }
 }
 
-fn get_slates_dir() -> String {
+fn get_data_dir() -> String {
     let nfl_sims_path =
         env::var("NFL_SIMS_PATH").expect("could not find environment variable NFL_SIMS_PATH");
-    format!("{}/data/slates", nfl_sims_path)
+    format!("{}/data", nfl_sims_path)
+}
+
+fn get_slates_dir() -> String {
+    format!("{}/slates", get_data_dir())
+}
+
+/// the slate's `slate.json` if it has one, otherwise its params tables
+fn params_path(slate_dir: &str) -> String {
+    let slate_path = format!("{}/slate.json", slate_dir);
+    match std::path::Path::new(&slate_path).exists() {
+        true => slate_path,
+        false => format!("{}/params", slate_dir),
+    }
+}
+
+fn load_params(params_path: &str) -> Vec<GameParamsDistribution> {
+    match params_path.ends_with(".json") {
+        true => SlateFile::load(params_path).to_game_params(),
+        false => GameParams::load(params_path.to_string()),
+    }
+}
+
+/// joins the baselines for the games in `schedule.csv`, with any fixes from
+/// `overrides.csv`, into the slate's params tables
+fn build_params(slate_dir: String) {
+    let overrides_path = format!("{}/overrides.csv", slate_dir);
+    build_slate_params(
+        &format!("{}/baselines", get_data_dir()),
+        &format!("{}/schedule.csv", slate_dir),
+        std::path::Path::new(&overrides_path)
+            .exists()
+            .then_some(overrides_path.as_str()),
+        &format!("{}/params", slate_dir),
+    );
+}
+
+/// sims the slate, or each team against an average opponent, and flags
+/// league rates that drifted outside the baseline tolerances
+fn run_diagnostics(slate_dir: String, vs_average: bool) {
+    let game_params_vec = load_params(&params_path(&slate_dir));
+    let game_params_vec = match vs_average {
+        true => vs_average_opponent(&game_params_vec),
+        false => game_params_vec,
+    };
+    let baselines = Baseline::load(&format!("{}/baselines", get_data_dir()));
+    let box_scores = sim_box_scores_rayon(
+        N_DIAGNOSTIC_SIMS,
+        &game_params_vec,
+        SIM_INJURIES,
+        "Diagnostics",
+    );
+    let checks = check_rates(&box_scores, &baselines);
+    write_diagnostics(&format!("{}/projections", slate_dir), &checks);
 }
 
 fn run_slate(slate_dir: String) {
-    env_logger::init();
-    let game_params_vec = GameParams::load(format!("{}/params", &slate_dir));
+    let params_path = params_path(&slate_dir);
+    let game_params_vec =
+        GameParams::with_share_sampling(&load_params(&params_path), SHARE_SAMPLING);
     let player_meta = GameParams::player_meta(&game_params_vec);
 
-    let game_params_vec = burn_in_params(5_000, &game_params_vec, &player_meta);
+    let game_params_vec = cached_burn_in_params(
+        &params_path,
+        &format!("{}/burn_in", &slate_dir),
+        &game_params_vec,
+        &player_meta,
+        &BurnInConfig::default(),
+    );
+    let game_params_vec = calibrate_to_market(&game_params_vec, &CalibrationConfig::default());
+    let props_path = format!("{}/props.csv", &slate_dir);
+    let game_params_vec = match std::path::Path::new(&props_path).exists() {
+        true => {
+            let props = PropLoader::load(&props_path);
+            let (game_params_vec, fits) =
+                calibrate_props(&game_params_vec, &props, &PropCalibrationConfig::default());
+            write_prop_calibration_report(&slate_dir, &fits);
+            game_params_vec
+        }
+        false => game_params_vec,
+    };
 
     let opponents: std::collections::HashMap<String, String> =
         GameParams::opponents_map(&game_params_vec);
 
-    let box_scores = sim_box_scores_rayon(N_SIMS, &game_params_vec, SIM_INJURIES, "Sims");
+    let box_scores = match CHECK_INVARIANTS {
+        true => sim_box_scores_checked(
+            N_SIMS,
+            &game_params_vec,
+            SIM_INJURIES,
+            MAX_RETRIES,
+            "Checked sims",
+        ),
+        false => sim_box_scores_rayon(N_SIMS, &game_params_vec, SIM_INJURIES, "Sims"),
+    };
     let projections = accumulate_projections(&box_scores, false);
     let proj_writer = ProjectionsWriter::new(&slate_dir, &projections, &player_meta, &opponents);
     proj_writer.write_projections();
+    write_json_projections(
+        &slate_dir,
+        &RunMetadata::new(SLATE_ID, box_scores.len() as u32, SIM_INJURIES),
+        &projections,
+        &player_meta,
+        &opponents,
+    );
+    write_drive_summary(&slate_dir, &box_scores);
+    write_game_lines(&slate_dir, &box_scores);
+    write_period_lines(&slate_dir, &box_scores);
+    write_touchdown_scorers(&slate_dir, &box_scores, &player_meta, TD_SCORER_VIG);
+    write_prop_ladders(
+        &slate_dir,
+        &box_scores,
+        &player_meta,
+        &PropLadderConfig::default(),
+    );
+    let n_samples = N_SAMPLES.min(box_scores.len());
+    write_samples(
+        &slate_dir,
+        &sample_box_scores(&box_scores[..n_samples], &player_meta),
+    );
 }